@@ -0,0 +1,16 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * settings of the api, replaced with `set_config`; fields left out get their defaults
+ */
+export type ApiConfig = { 
+/**
+ * most bytes `read_bytes` returns at once
+ */
+max_read_bytes: bigint, 
+/**
+ * `read_bytes` of at least this many bytes map the file instead of reading it (unix), 0 never maps
+ *
+ * Off by default: a file truncated by another process while it is copied kills the process.
+ */
+mmap_min_bytes: bigint, };
@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * result of `find_bytes`
+ */
+export type BytesResult = { path: string, 
+/**
+ * bytes of the pattern, the length of each match
+ */
+len: number, offsets: Array<bigint>, 
+/**
+ * stopped at `max_hits`
+ */
+truncated: boolean, };
@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * total of a folder tree; also the progress of `compute_dir_size`
+ */
+export type DirSize = { path: string, sz: bigint, files: bigint, dirs: bigint, canceled?: boolean, };
@@ -0,0 +1,5 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiskEventKind } from "./DiskEventKind";
+import type { DiskInfo } from "./DiskInfo";
+
+export type DiskEvent = { kind: DiskEventKind, disk: DiskInfo, };
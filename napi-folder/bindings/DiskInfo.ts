@@ -1,3 +1,4 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { DiskKind } from "./DiskKind";
 
-export type DiskInfo = { path: string, };
+export type DiskInfo = { path: string, nm: string, display_nm: string, fs: string, kind: DiskKind, total: bigint, available: bigint, removable: boolean, read_only: boolean, };
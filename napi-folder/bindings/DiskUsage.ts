@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { UsageNode } from "./UsageNode";
+
+export type DiskUsage = { path: string, top_n: number, depth: number, root?: UsageNode, canceled?: boolean, };
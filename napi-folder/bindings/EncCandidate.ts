@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * encoding offered to reopen a text file with
+ */
+export type EncCandidate = { enc: string, err_cnt: number, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { GpsPos } from "./GpsPos";
+
+/**
+ * EXIF tags of `read_image_info`, tags the file doesn't have are left out
+ */
+export type ExifInfo = { make?: string, model?: string, lens?: string, 
+/**
+ * seconds
+ */
+exposure_time?: number, f_number?: number, iso?: number, 
+/**
+ * mm
+ */
+focal_length?: number, 
+/**
+ * `2024-05-01T12:34:56`, with `+09:00` if the camera recorded its offset
+ */
+taken_at?: string, 
+/**
+ * 1 to 8, 5 to 8 swap width and height for display
+ */
+orientation?: number, gps?: GpsPos, };
@@ -0,0 +1,19 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Item } from "./Item";
+
+/**
+ * file or folder found by `find`
+ */
+export type FindHit = { 
+/**
+ * relative to the root, `/` separated
+ */
+rel: string, 
+/**
+ * higher is better, 0 for glob and regex
+ */
+score: bigint, 
+/**
+ * ranges of `rel` that matched, in UTF-16 code units like JS string indices
+ */
+hl: Array<[number, number]>, item: Item, };
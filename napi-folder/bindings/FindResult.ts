@@ -0,0 +1,12 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { FindHit } from "./FindHit";
+
+export type FindResult = { root: string, 
+/**
+ * the best `max_results` hits
+ */
+hits: Array<FindHit>, 
+/**
+ * hits found in total
+ */
+total: number, canceled?: boolean, };
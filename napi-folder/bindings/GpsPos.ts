@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * position of a photo in degrees, south and west negative
+ */
+export type GpsPos = { lat: number, lon: number, 
+/**
+ * meters above sea level
+ */
+alt?: number, };
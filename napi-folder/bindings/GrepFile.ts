@@ -0,0 +1,11 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchHit } from "./SearchHit";
+
+/**
+ * matches of `grep` in one file, sent while the search goes on
+ */
+export type GrepFile = { path: string, 
+/**
+ * relative to the root, `/` separated
+ */
+rel: string, enc: string, hits: Array<SearchHit>, truncated: boolean, };
@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * options of `grep`
+ */
+export type GrepOpt = { 
+/**
+ * the pattern is a regex, else a literal
+ */
+regex?: boolean, case_sensitive?: boolean, 
+/**
+ * file name globs like `*.rs`, or relative path globs like `src/*.rs`
+ */
+include?: Array<string>, 
+/**
+ * globs of files and folders to skip, e.g. `node_modules`
+ */
+exclude?: Array<string>, 
+/**
+ * search hidden files and folders
+ */
+hidden?: boolean, 
+/**
+ * default 10000
+ */
+max_hits?: bigint, 
+/**
+ * default 100
+ */
+max_file_hits?: number, };
@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+export type GrepSummary = { root: string, 
+/**
+ * files searched
+ */
+files: bigint, matched_files: bigint, hits: bigint, 
+/**
+ * files skipped as binary
+ */
+binaries: bigint, 
+/**
+ * reached `max_hits`
+ */
+truncated: boolean, canceled?: boolean, };
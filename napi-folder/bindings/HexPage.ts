@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { HexRow } from "./HexRow";
+
+/**
+ * page of `read_hex`
+ */
+export type HexPage = { path: string, sz: bigint, 
+/**
+ * start of the first row
+ */
+offset: bigint, 
+/**
+ * `offset` of the next page
+ */
+next_offset: bigint, bytes_per_row: number, rows: Array<HexRow>, eof: boolean, };
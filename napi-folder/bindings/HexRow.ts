@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * row of `read_hex`
+ */
+export type HexRow = { offset: bigint, 
+/**
+ * lowercase, space separated: `4d 5a 90 00`
+ */
+hex: string, 
+/**
+ * printable ASCII, `.` for other bytes
+ */
+ascii: string, };
@@ -0,0 +1,23 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { ExifInfo } from "./ExifInfo";
+
+/**
+ * returned by `read_image_info`, from the headers only
+ */
+export type ImageInfo = { path: string, 
+/**
+ * usual extension of the format: `png`, `jpg`, `gif`, `bmp`, `webp` or `tiff`
+ */
+format: string, width: number, height: number, 
+/**
+ * `Rgb8`, `Rgba16`, `L8`...
+ */
+color_type: string, 
+/**
+ * bits per channel
+ */
+bit_depth: number, 
+/**
+ * frames of animations, not set for still images
+ */
+frames?: number, exif?: ExifInfo, };
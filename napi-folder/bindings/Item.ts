@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type Item = { nm: string, dir: boolean, ext?: string, mt?: string, sz?: bigint, tm?: bigint, items?: Array<Item>, };
+export type Item = { nm: string, dir: boolean, ext?: string, mt?: string, sz?: bigint, tm?: bigint, ctime?: bigint, atime?: bigint, mode?: string, mode_oct?: string, owner?: string, group?: string, hidden?: boolean, readonly?: boolean, symlink?: string, broken?: boolean, inode?: bigint, nlink?: bigint, child_cnt?: number, has_children?: boolean, width?: number, height?: number, taken_at?: string, items?: Array<Item>, };
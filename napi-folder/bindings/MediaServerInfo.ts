@@ -0,0 +1,10 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * returned by `start_media_server`
+ */
+export type MediaServerInfo = { port: number, token: string, 
+/**
+ * append the percent-encoded path of a file: `url + encodeURIComponent(path)`
+ */
+url: string, };
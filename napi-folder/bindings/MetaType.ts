@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type MetaType = "Sz" | "Tm" | "Mt" | "Ext";
+export type MetaType = "Sz" | "Tm" | "Mt" | "Ext" | "Ctime" | "Atime" | "Mode" | "Owner" | "Group" | "Hidden" | "Readonly" | "Symlink" | "Inode" | "Nlink" | "ChildCnt" | "HasChildren" | "DirSz" | "Dim";
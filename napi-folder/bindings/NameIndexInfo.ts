@@ -0,0 +1,14 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * persisted file name index of a root; also the progress of `update_name_index`
+ */
+export type NameIndexInfo = { root: string, files: bigint, dirs: bigint, 
+/**
+ * folders read by the update, the others were unchanged
+ */
+read_dirs?: bigint, 
+/**
+ * seconds, when the index was updated, 0 while updating
+ */
+tm: bigint, canceled?: boolean, };
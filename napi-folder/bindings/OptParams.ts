@@ -2,4 +2,4 @@
 import type { MetaType } from "./MetaType";
 import type { OrdItem } from "./OrdItem";
 
-export type OptParams = { path_str?: string, meta_types?: Array<MetaType>, ordering?: Array<OrdItem>, skip_n?: number, take_n?: number, is_pretty?: boolean, cache_nm?: string, };
+export type OptParams = { path_str?: string, meta_types?: Array<MetaType>, ordering?: Array<OrdItem>, skip_n?: number, take_n?: number, is_pretty?: boolean, cache_nm?: string, depth?: number, max_nodes?: number, filter?: string, };
@@ -1,5 +1,10 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 import type { OrderAsc } from "./OrderAsc";
 import type { OrderBy } from "./OrderBy";
+import type { OrderCmp } from "./OrderCmp";
 
-export type OrdItem = { nm: OrderBy, asc: OrderAsc, };
+export type OrdItem = { nm: OrderBy, asc: OrderAsc, cmp?: OrderCmp, 
+/**
+ * BCP 47 tag for `OrderCmp::Locale`, e.g. `ko`, `de`
+ */
+locale?: string, };
@@ -1,3 +1,3 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
 
-export type OrderBy = "Dir" | "Nm" | "Sz" | "Tm" | "Mt" | "Ext";
+export type OrderBy = "Dir" | "Nm" | "Sz" | "Tm" | "Mt" | "Ext" | "Ctime" | "Atime" | "Mode" | "Owner" | "Group" | "Hidden" | "Readonly" | "Symlink" | "Inode" | "Nlink" | "ChildCnt" | "Dim" | "TakenAt";
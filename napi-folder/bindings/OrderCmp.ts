@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * how `Nm`, `Ext` and `Mt` are compared
+ */
+export type OrderCmp = "Lower" | "Natural" | "Locale" | "Case";
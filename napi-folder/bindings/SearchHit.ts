@@ -0,0 +1,30 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * match of `search_in_file`/`grep`
+ */
+export type SearchHit = { 
+/**
+ * 0 based
+ */
+line_no: bigint, 
+/**
+ * byte offset in the file, for `read_text_range`
+ */
+offset: bigint, 
+/**
+ * char offset in the line
+ */
+col: number, 
+/**
+ * chars of the match
+ */
+len: number, 
+/**
+ * the line around the match
+ */
+snippet: string, 
+/**
+ * char range of the match in `snippet`
+ */
+hl: [number, number], };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchHit } from "./SearchHit";
+
+export type SearchResult = { path: string, enc: string, hits: Array<SearchHit>, 
+/**
+ * stopped at `max_hits`
+ */
+truncated: boolean, };
@@ -0,0 +1,7 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TailKind } from "./TailKind";
+
+/**
+ * event of `tail`, `lines` is set for `Lines`
+ */
+export type TailEvent = { kind: TailKind, lines?: Array<string>, };
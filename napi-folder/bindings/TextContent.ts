@@ -1,3 +1,34 @@
 // This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { EncCandidate } from "./EncCandidate";
+import type { LineEnding } from "./LineEnding";
+import type { TextStats } from "./TextStats";
 
-export type TextContent = { path: string, mimetype: string, enc?: string, text?: string, };
+export type TextContent = { path: string, mimetype: string, enc?: string, text?: string, 
+/**
+ * the file starts with a byte order mark, which also decided `enc`
+ */
+bom?: boolean, 
+/**
+ * `false` if the detected `enc` is likely to be wrong, not set if the encoding was given
+ */
+confident?: boolean, 
+/**
+ * malformed sequences, replaced by U+FFFD in lossy mode
+ */
+err_cnt?: number, 
+/**
+ * from the first `DETECT_SZ` bytes, not set if the encoding was given
+ */
+candidates?: Array<EncCandidate>, 
+/**
+ * the most used line ending
+ */
+line_ending?: LineEnding, 
+/**
+ * mtime in nanoseconds as a decimal string (too big for a JS number), the `expected_mtime` of `write_text`
+ */
+tm?: string, 
+/**
+ * set with `text`
+ */
+stats?: TextStats, };
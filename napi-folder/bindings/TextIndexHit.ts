@@ -0,0 +1,15 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { SearchHit } from "./SearchHit";
+
+/**
+ * document of `search_text_index`
+ */
+export type TextIndexHit = { path: string, rel: string, 
+/**
+ * BM25, higher is better
+ */
+score: number, 
+/**
+ * the first lines with query words, empty if the file changed since it was indexed
+ */
+hits: Array<SearchHit>, };
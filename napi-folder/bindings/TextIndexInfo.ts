@@ -0,0 +1,18 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * persisted full-text index of a root; also the progress of `update_text_index`
+ */
+export type TextIndexInfo = { root: string, docs: bigint, 
+/**
+ * distinct words
+ */
+terms: bigint, 
+/**
+ * files read by the update, the others were unchanged
+ */
+read_docs?: bigint, 
+/**
+ * seconds, when the index was updated, 0 while updating
+ */
+tm: bigint, canceled?: boolean, };
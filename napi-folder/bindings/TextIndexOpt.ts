@@ -0,0 +1,22 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * what `update_text_index` indexes
+ */
+export type TextIndexOpt = { 
+/**
+ * globs of the files to index, e.g. `*.md`; all text files if empty
+ */
+include?: Array<string>, 
+/**
+ * globs of files and folders to skip
+ */
+exclude?: Array<string>, 
+/**
+ * index hidden files and folders
+ */
+hidden?: boolean, 
+/**
+ * bytes, larger files are skipped; 4MB if not set
+ */
+max_file_sz?: bigint, };
@@ -0,0 +1,8 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { TextIndexHit } from "./TextIndexHit";
+
+export type TextIndexResult = { root: string, hits: Array<TextIndexHit>, 
+/**
+ * matching documents in total
+ */
+total: number, };
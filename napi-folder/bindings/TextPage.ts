@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * page of `read_text_range`, `next_offset`/`next_line` continue with the following page
+ */
+export type TextPage = { path: string, enc: string, sz: bigint, tot_lines: bigint, offset: bigint, next_offset: bigint, line_start: bigint, next_line: bigint, line_cnt: bigint, had_errors: boolean, eof: boolean, text: string, };
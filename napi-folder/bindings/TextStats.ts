@@ -0,0 +1,31 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { Indent } from "./Indent";
+
+/**
+ * status bar numbers of a text, lines end at LF or CRLF, or at CR in a text without LF
+ */
+export type TextStats = { line_cnt: number, 
+/**
+ * runs of non-whitespace
+ */
+word_cnt: number, char_cnt: number, 
+/**
+ * more than one kind of line ending, the most used one is `TextContent.line_ending`
+ */
+mixed_ending: boolean, indent?: Indent, 
+/**
+ * non-blank lines indented with a tab
+ */
+tab_lines: number, 
+/**
+ * non-blank lines indented with a space
+ */
+space_lines: number, trailing_ws_lines: number, 
+/**
+ * chars of the longest line
+ */
+longest_line: number, 
+/**
+ * 0 based
+ */
+longest_line_no: number, ends_with_nl: boolean, };
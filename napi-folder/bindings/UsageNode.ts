@@ -0,0 +1,6 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+
+/**
+ * node of `analyze_disk_usage`, a file or a folder with its largest children
+ */
+export type UsageNode = { nm: string, dir: boolean, sz: bigint, files: bigint, dirs: bigint, pct: number, pct_parent: number, others_sz?: bigint, others_cnt?: bigint, children?: Array<UsageNode>, };
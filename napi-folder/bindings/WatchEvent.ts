@@ -0,0 +1,4 @@
+// This file was generated by [ts-rs](https://github.com/Aleph-Alpha/ts-rs). Do not edit this file manually.
+import type { WatchKind } from "./WatchKind";
+
+export type WatchEvent = { kind: WatchKind, path: string, from?: string, };
@@ -8,3 +8,41 @@ export {OrdItem} from "./OrdItem"
 export {TextContent} from "./TextContent"
 export {HomeType} from "./HomeType"
 export {DiskInfo} from "./DiskInfo"
+export {WatchKind} from "./WatchKind"
+export {WatchEvent} from "./WatchEvent"
+export {OrderCmp} from "./OrderCmp"
+export {DirSize} from "./DirSize"
+export {UsageNode} from "./UsageNode"
+export {DiskUsage} from "./DiskUsage"
+export {DiskKind} from "./DiskKind"
+export {DiskEventKind} from "./DiskEventKind"
+export {DiskEvent} from "./DiskEvent"
+export {TextPage} from "./TextPage"
+export {TailKind} from "./TailKind"
+export {TailEvent} from "./TailEvent"
+export {EncCandidate} from "./EncCandidate"
+export {LineEnding} from "./LineEnding"
+export {TextStats} from "./TextStats"
+export {Indent} from "./Indent"
+export {SearchHit} from "./SearchHit"
+export {SearchResult} from "./SearchResult"
+export {GrepOpt} from "./GrepOpt"
+export {GrepFile} from "./GrepFile"
+export {GrepSummary} from "./GrepSummary"
+export {FindMode} from "./FindMode"
+export {FindHit} from "./FindHit"
+export {FindResult} from "./FindResult"
+export {NameIndexInfo} from "./NameIndexInfo"
+export {TextIndexOpt} from "./TextIndexOpt"
+export {TextIndexInfo} from "./TextIndexInfo"
+export {TextIndexHit} from "./TextIndexHit"
+export {TextIndexResult} from "./TextIndexResult"
+export {HexRow} from "./HexRow"
+export {HexPage} from "./HexPage"
+export {BytesResult} from "./BytesResult"
+export {ApiConfig} from "./ApiConfig"
+export {MediaServerInfo} from "./MediaServerInfo"
+export {ThumbFormat} from "./ThumbFormat"
+export {GpsPos} from "./GpsPos"
+export {ExifInfo} from "./ExifInfo"
+export {ImageInfo} from "./ImageInfo"
@@ -5,7 +5,220 @@
 
 export declare class FolderApi {
   constructor()
-  readText(pathStr: string): Promise<string>
+  /**
+   *
+   * read a text file up to 5MB
+   *
+   * # arg
+   * - path_str
+   * - encoding: decode with this encoding instead of the detected one, e.g. one of `candidates`
+   * - lossy: return text with U+FFFD for malformed sequences instead of no text
+   *
+   * returns a json `TextContent`
+   */
+  readText(pathStr: string, encoding?: string | undefined | null, lossy?: boolean | undefined | null): Promise<string>
+  /**
+   *
+   * save a text file, refusing if it changed since it was read
+   *
+   * # arg
+   * - path_str
+   * - text
+   * - encoding: `enc` of `read_text`
+   * - line_ending: `Lf`, `Crlf` or `Cr`, the `line_ending` of `read_text`; `text` is written as is if not given
+   * - expected_mtime: `tm` of `read_text`, nanoseconds as a decimal string
+   *
+   * returns the new mtime like `tm`
+   */
+  writeText(pathStr: string, text: string, encoding: string, lineEnding?: 'Lf' | 'Crlf' | 'Cr', expectedMtime?: string): Promise<string>
+  /**
+   *
+   * find text in a file of any size, for Ctrl+F
+   *
+   * # arg
+   * - path_str
+   * - pattern
+   * - regex: `pattern` is a regex (default false)
+   * - case_sensitive: default false
+   * - max_hits: default 1000
+   *
+   * returns a json `SearchResult`
+   */
+  searchInFile(pathStr: string, pattern: string, regex?: boolean | undefined | null, caseSensitive?: boolean | undefined | null, maxHits?: number | undefined | null): Promise<string>
+  /**
+   *
+   * search the contents of the files under a folder
+   *
+   * # arg
+   * - root
+   * - pattern
+   * - json_opt: json `GrepOpt`
+   * - callback: called with a json `GrepFile` for each file with matches
+   *
+   * returns a json `GrepSummary`, `canceled` is set if `cancel_grep` stopped it
+   */
+  grep(root: string, pattern: string, jsonOpt: string | undefined | null, callback: (json: string) => void): Promise<string>
+  /** stop a running `grep` of `root`; returns `false` if none is running */
+  cancelGrep(root: string): boolean
+  /**
+   *
+   * find files and folders under a folder by their relative path
+   *
+   * # arg
+   * - root
+   * - query
+   * - mode: `Glob`, `Regex`, `Fuzzy` (default) or `Substring`
+   * - max_results: default 1000
+   * - callback: called with a json `FindHit[]` for each folder with hits
+   *
+   * returns a json `FindResult`, `canceled` is set if `cancel_find` stopped it
+   */
+  find(root: string, query: string, mode?: 'Glob' | 'Regex' | 'Fuzzy' | 'Substring', maxResults?: number, callback?: (json: string) => void): Promise<string>
+  /** stop a running `find` under `root`; returns `false` if none is running */
+  cancelFind(root: string): boolean
+  /**
+   *
+   * build or refresh the persisted file name index of a folder or disk
+   *
+   * # arg
+   * - root: a path of `get_disks` or `get_home_dir`
+   * - callback: called with a json `NameIndexInfo` while walking
+   *
+   * returns a json `NameIndexInfo`, `canceled` is set if `cancel_name_index` stopped it
+   */
+  updateNameIndex(root: string, callback?: (json: string) => void): Promise<string>
+  /** stop a running `update_name_index` of `root`, the previous index is kept; returns `false` if none is running */
+  cancelNameIndex(root: string): boolean
+  /** json `NameIndexInfo` of the last update, or `null` if `root` was never indexed */
+  getNameIndexInfo(root: string): Promise<string | null>
+  /**
+   *
+   * search the file name index of a folder, like `find` but without walking it
+   *
+   * # arg
+   * - root: as given to `update_name_index`
+   * - query
+   * - mode: `Glob`, `Regex`, `Fuzzy` or `Substring` (default)
+   * - max_results: default 1000
+   *
+   * returns a json `FindResult`
+   */
+  searchNameIndex(root: string, query: string, mode?: 'Glob' | 'Regex' | 'Fuzzy' | 'Substring', maxResults?: number): Promise<string>
+  /**
+   *
+   * build or refresh the full-text index of a folder, opt-in per folder
+   *
+   * # arg
+   * - root
+   * - json_opt: json `TextIndexOpt`, the options of the last update if not given
+   * - callback: called with a json `TextIndexInfo` while indexing
+   *
+   * returns a json `TextIndexInfo`, `canceled` is set if `cancel_text_index` stopped it
+   */
+  updateTextIndex(root: string, jsonOpt?: string, callback?: (json: string) => void): Promise<string>
+  /** stop a running `update_text_index` of `root`, the previous index is kept; returns `false` if none is running */
+  cancelTextIndex(root: string): boolean
+  /** delete the full-text index of a folder; `false` if there was none */
+  removeTextIndex(root: string): Promise<boolean>
+  /** json `TextIndexInfo` of the last update, or `null` if `root` was never indexed */
+  getTextIndexInfo(root: string): Promise<string | null>
+  /**
+   *
+   * search the full-text index of a folder
+   *
+   * # arg
+   * - root: as given to `update_text_index`
+   * - query: words that must all be in a document, each also matches longer words it starts
+   * - max_results: default 100
+   * - max_snippets: matching lines per document, default 3
+   *
+   * returns a json `TextIndexResult`
+   */
+  searchTextIndex(root: string, query: string, maxResults?: number | undefined | null, maxSnippets?: number | undefined | null): Promise<string>
+  /**
+   *
+   * read a page of a large text file
+   *
+   * # arg
+   * - path_str
+   * - offset: byte offset, used if `line_start` is not given (`next_offset` of the previous page)
+   * - line_start: 0 based line number
+   * - line_cnt: max lines of the page
+   * - max_bytes: max bytes of the page (default 1MB), a longer line is split over pages
+   *
+   * returns a json `TextPage`
+   */
+  readTextRange(pathStr: string, offset?: number | undefined | null, lineStart?: number | undefined | null, lineCnt?: number | undefined | null, maxBytes?: number | undefined | null): Promise<string>
+  /**
+   *
+   * hex dump of a part of any file
+   *
+   * # arg
+   * - path_str
+   * - offset: byte offset, moved back to its row start (`next_offset` of the previous page)
+   * - length: bytes of the page, default 4KB, at most 256KB
+   * - bytes_per_row: default 16, at most 64
+   *
+   * returns a json `HexPage`
+   */
+  readHex(pathStr: string, offset?: number | undefined | null, length?: number | undefined | null, bytesPerRow?: number | undefined | null): Promise<string>
+  /**
+   *
+   * offsets of a byte pattern in a file
+   *
+   * # arg
+   * - path_str
+   * - pattern: hex digits like `ff d8 ff` if `hex`, else text
+   * - hex: default false
+   * - start: byte offset to search from
+   * - max_hits: default 1000
+   *
+   * returns a json `BytesResult`
+   */
+  findBytes(pathStr: string, pattern: string, hex?: boolean | undefined | null, start?: number | undefined | null, maxHits?: number | undefined | null): Promise<string>
+  /**
+   *
+   * raw bytes of a file as a `Buffer`, without json
+   *
+   * # arg
+   * - path_str
+   * - offset: default 0
+   * - length: default the rest of the file; more than `max_read_bytes` of the config is an error
+   */
+  readBytes(pathStr: string, offset?: number | undefined | null, length?: number | undefined | null): Promise<Buffer>
+  /**
+   *
+   * thumbnail of an image, cached as a freedesktop thumbnail
+   *
+   * # arg
+   * - path_str: PNG, JPEG, GIF, BMP, WebP or TIFF
+   * - max_size: longest side, default 256, at most 1024; smaller images are not scaled up
+   * - format: `Png` (default) or `Jpeg`
+   */
+  getThumbnail(pathStr: string, maxSize?: number, format?: 'Png' | 'Jpeg'): Promise<Buffer>
+  /**
+   *
+   * width/height, color type, bit depth, frames and EXIF of an image, without decoding its pixels
+   *
+   * returns a json `ImageInfo`
+   */
+  readImageInfo(pathStr: string): Promise<string>
+  /**
+   *
+   * serve files over http on localhost so `<audio>`/`<video>` can seek with `Range`
+   *
+   * # arg
+   * - roots: folders or files the returned token may read
+   *
+   * returns a json `MediaServerInfo`, files are at `url + encodeURIComponent(path)`
+   */
+  startMediaServer(roots: Array<string>): Promise<string>
+  /** revoke a token of `start_media_server`, the server stops with the last one */
+  stopMediaServer(token: string): boolean
+  /** json `ApiConfig` */
+  getConfig(): string
+  /** replace the config with a json `ApiConfig`, missing fields get their defaults; returns the new config */
+  setConfig(jsonConfig: string): string
   readFolder(jsonParams: string): Promise<string>
   /**
    *
@@ -33,4 +246,65 @@ export declare class FolderApi {
   getState(key: string, defaultVal?: string | undefined | null): Promise<string | null>
   getHomeDir(): Promise<string>
   getDisks(): Promise<string>
+  /**
+   *
+   * watch a folder
+   *
+   * # arg
+   * - path_str
+   * - recursive: watch sub folders too
+   * - callback: called with a json `WatchEvent[]` after changes settle
+   *
+   * returns the id for `unwatch`
+   */
+  watch(pathStr: string, recursive: boolean, callback: (json: string) => void): number
+  /**
+   *
+   * watch mounted disks (USB sticks, network drives)
+   *
+   * # arg
+   * - callback: called with a json `DiskEvent[]` when disks are added or removed
+   *
+   * returns the id for `unwatch`
+   */
+  watchDisks(callback: (json: string) => void): number
+  /**
+   *
+   * follow a log file like `tail -f`
+   *
+   * # arg
+   * - path_str
+   * - encoding: `enc` of `read_text` to decode with; detected like `read_text` does if not given
+   * - callback: called with a json `TailEvent[]` when lines are appended or the file is truncated or rotated
+   *
+   * returns the id for `unwatch`
+   */
+  tail(pathStr: string, encoding: string | undefined | null, callback: (json: string) => void): Promise<number>
+  /** stop a `watch`, `watch_disks` or `tail` */
+  unwatch(id: number): boolean
+  /**
+   *
+   * total size of a folder tree
+   *
+   * # arg
+   * - path_str
+   * - callback: called with a json `DirSize` while walking
+   *
+   * returns a json `DirSize`, `canceled` is set if `cancel_dir_size` stopped it
+   */
+  computeDirSize(pathStr: string, callback?: (json: string) => void): Promise<string>
+  /**
+   *
+   * largest files and folders under a folder, for treemap/sunburst views
+   *
+   * # arg
+   * - path_str
+   * - top_n: children kept per folder, the rest is summed in `others_sz` (default 20)
+   * - depth: levels of children (default 3)
+   * - callback: called with a json `DirSize` while walking
+   *
+   * returns a json `DiskUsage`
+   */
+  analyzeDiskUsage(pathStr: string, topN?: number, depth?: number, callback?: (json: string) => void): Promise<string>
+  cancelDirSize(pathStr: string): boolean
 }
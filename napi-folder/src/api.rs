@@ -1,42 +1,100 @@
-use std::{cmp};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::{PathBuf};
 use std::path::Component::Prefix;
-use std::sync::OnceLock;
-use std::time::SystemTime;
-use tokio;
-use tokio::io::AsyncReadExt;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::{Duration, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use mime_guess::{from_path};
 use encoding_rs::Encoding;
-use chardetng::EncodingDetector;
 use moka::future::Cache;
-use dirs_next;
-use sysinfo::Disks;
+use sysinfo::{Disk, Disks};
+use notify::{RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
 
-use crate::models::{ CacheKey, CacheVal,
-                    Item, Folder, Params, TextContent, ApiError, HomeType, DiskInfo};
+use crate::models::{ CacheKey, CacheVal, CacheDirSzKey, MetaType,
+                    Item, Folder, Params, TextContent, TextPage, ApiError, HomeType, DiskInfo, WatchEvent, DirSize, DiskUsage, DiskKind, DiskEvent, TailEvent, LineEnding, SearchResult, GrepOpt, GrepFile, GrepSummary, FindMode, FindHit, FindResult, NameIndexInfo,
+                    TextIndexOpt, TextIndexInfo, TextIndexHit, TextIndexResult, HexPage, BytesResult, ApiConfig, MediaServerInfo, ThumbFormat, ImageInfo};
 use crate::path_ext::PathExt;
 use crate::system_time_ext::SystemTimeExt;
-use crate::dir::{get_items_win32, update_items, sort_items, update_max_len_nm };
+use crate::filter::Filter;
+use crate::dir::{list_items, sort_items, slice_items, fill_tree, update_max_len_nm, update_dims};
+use crate::watch::{to_watch_events, affected_folders, diff_disks};
+use crate::walk::{SizeWalker, WalkStat, update_pct};
+use crate::text::{LineIndex, Tailer, read_page, snap_offset, detect_candidates, count_errors,
+                  detect_line_ending, convert_line_ending, encode_text, text_stats, detect_encoding, index_file, DEFAULT_PAGE_BYTES, DETECT_SZ};
+use crate::search::{Matcher, Grep, search_lines};
+use crate::find::{Finder, Query};
+use crate::name_index::{NameIndex, IndexStat};
+use crate::text_index::{TextIndex, TextIndexStat, word_matcher};
+use crate::bytes::read_range;
+use crate::media::MediaServer;
+use crate::image_info::read_image_info;
+use crate::thumb::{bucket, file_uri, thumb_path, load_cached, make_thumbnail, save_thumbnail, encode, fit, MAX_THUMB_SIZE};
+use crate::jobs::Jobs;
+use crate::hex::{read_hex, find_bytes, parse_pattern, DEFAULT_BYTES_PER_ROW, DEFAULT_HEX_LEN};
+
+const WATCH_DEBOUNCE_MS: u64 = 200;
+const DISK_POLL_MS: u64 = 2000;
+const TAIL_POLL_MS: u64 = 300;
 
 static INSTANCE: OnceLock<Api> = OnceLock::new();
 
 pub fn get_instance() -> &'static Api {
-    INSTANCE.get_or_init(|| Api::new())
+    INSTANCE.get_or_init(Api::new)
 }
 
 pub struct Api {
     cache_folder: Cache<CacheKey, CacheVal>,
     // cache_paths: Cache<CachePathsKey, Vec<PathBuf>>,
     state: Cache<String, String>,
+    watchers: Mutex<HashMap<u32, Debouncer<RecommendedWatcher, RecommendedCache>>>,
+    watch_id: AtomicU32,
+    cache_dir_sz: Cache<CacheDirSzKey, DirSize>,
+    size_jobs: Jobs,
+    grep_jobs: Jobs,
+    find_jobs: Jobs,
+    index_jobs: Jobs,
+    text_index_jobs: Jobs,
+    /// stop flags of the threads of `watch_disks` and `tail`
+    pollers: Mutex<HashMap<u32, Arc<AtomicBool>>>,
+    /// persisted indexes, `<cache_dir>/line-index` etc.
+    cache_dir: PathBuf,
+    cache_line_index: Cache<String, LineIndex>,
+    cache_name_index: Cache<String, Arc<NameIndex>>,
+    cache_text_index: Cache<String, Arc<TextIndex>>,
+    config: Mutex<ApiConfig>,
+    media_server: Mutex<Option<MediaServer>>,
+    /// freedesktop thumbnail cache, `<HomeType::CacheDir>/thumbnails`
+    thumb_dir: PathBuf,
 }
 
 impl Default for Api {
     fn default() -> Self {
         Api {
-            cache_folder: Cache::new(100),
+            cache_folder: Cache::builder()
+                .max_capacity(100)
+                .support_invalidation_closures()
+                .build(),
             // cache_paths: Cache::new(100),
             state: Cache::new(100),
+            watchers: Mutex::new(HashMap::new()),
+            watch_id: AtomicU32::new(1),
+            cache_dir_sz: Cache::new(10_000),
+            size_jobs: Jobs::default(),
+            grep_jobs: Jobs::default(),
+            find_jobs: Jobs::default(),
+            index_jobs: Jobs::default(),
+            text_index_jobs: Jobs::default(),
+            pollers: Mutex::new(HashMap::new()),
+            cache_dir: dirs_next::cache_dir().unwrap_or_else(std::env::temp_dir).join("re-viewer"),
+            cache_line_index: Cache::new(100),
+            cache_name_index: Cache::new(10),
+            cache_text_index: Cache::new(10),
+            config: Mutex::new(ApiConfig::default()),
+            media_server: Mutex::new(None),
+            thumb_dir: dirs_next::cache_dir().unwrap_or_else(std::env::temp_dir).join("thumbnails"),
         }
     }
 }
@@ -45,11 +103,7 @@ impl Api {
 
     #[allow(dead_code)]
     pub fn new() -> Self {
-        Api {
-            cache_folder: Cache::new(100),
-            // cache_paths: Cache::new(100),
-            state: Cache::new(100),
-        }
+        Api::default()
     }
 
     pub async fn get_folder(&self, params: &Params) -> Result<Folder, ApiError> {
@@ -61,8 +115,13 @@ impl Api {
             skip_n,
             take_n,
             cache_nm,
+            filter,
             ..
         } = params.clone();
+        let opt_filter = match filter.as_deref().map(str::trim) {
+            Some(expr) if !expr.is_empty() => Some(Filter::parse(expr)?),
+            _ => None,
+        };
 
         let mut folder = Folder::default();
         let mut abs = std::path::absolute(PathBuf::from(path_str))?;
@@ -110,9 +169,11 @@ impl Api {
         folder.path_param = abs.to_string_lossy().into();
         folder.base_nm = base_dir;
 
-        let mut item = Item::default();
-        item.nm = item_name;
-        item.dir = !is_file;
+        let mut item = Item {
+            nm: item_name,
+            dir: !is_file,
+            ..Item::default()
+        };
         let mut system_time : Option<SystemTime> = None;
         match abs.metadata() {
             Ok(meta) => {
@@ -127,7 +188,8 @@ impl Api {
         folder.item = item;
 
         let mut sorted_items: Vec<Item>;
-        
+        // `modified<7d` goes stale while the folder mtime stays the same
+        let cache_nm = cache_nm.filter(|_| !opt_filter.as_ref().is_some_and(Filter::is_relative));
 
         if let Some(cache_nm_str) = cache_nm {
             let cache_key = CacheKey {
@@ -138,6 +200,8 @@ impl Api {
                     None => return Err(ApiError::Folder(String::from("Err SystemTime"))),
                 },
                 meta_types: meta_types.clone().into_iter().collect(),
+                filter: opt_filter.as_ref().and(filter.clone()),
+                keep_dirs: params.depth > 1,
             };
 
             sorted_items = match self.cache_folder.get(&cache_key).await {
@@ -145,46 +209,52 @@ impl Api {
                     if cache_val.ordering != ordering  {
                         // let mut items_cache = get_items_win32(abs.to_string_lossy().as_ref(), &meta_types).unwrap_or(vec![]);
                         // update_items(&mut items_cache, &meta_types);
-                        println!("sort");
                         sort_items(&mut cache_val.items, &ordering);
                         
                         // cache_val.items = items_cache;
                         self.cache_folder.insert(cache_key.clone(), cache_val.clone()).await;
-                    } else {
-                        println!("hit cache folder");
                     }
                     cache_val.items
                 }
                 None => {
-                    println!("read folder");
-                    let mut items_new = get_items_win32(abs.to_string_lossy().as_ref(), &meta_types).unwrap_or(vec![]);
-                    update_items(&mut items_new, &meta_types);
+                    let mut items_new = list_items(abs.to_string_lossy().as_ref(), &meta_types, opt_filter.as_ref(), params.depth > 1).unwrap_or(vec![]);
+                    let complete = update_dims(abs.to_string_lossy().as_ref(), &mut items_new, &meta_types);
 
                     sort_items(&mut items_new, &ordering);
 
-                    let cache_val = CacheVal {
-                        ordering: ordering.clone(),
-                        items: items_new.clone(),
-                    };
-                    self.cache_folder.insert(cache_key.clone(), cache_val.clone()).await;
+                    // dimensions cut off by the time budget are read again next time
+                    if complete {
+                        let cache_val = CacheVal {
+                            ordering: ordering.clone(),
+                            items: items_new.clone(),
+                        };
+                        self.cache_folder.insert(cache_key.clone(), cache_val.clone()).await;
+                    }
                     items_new
                 }
             };
         } else {
-            sorted_items = get_items_win32(abs.to_string_lossy().as_ref(), &meta_types).unwrap_or(vec![]);
+            sorted_items = list_items(abs.to_string_lossy().as_ref(), &meta_types, opt_filter.as_ref(), params.depth > 1).unwrap_or(vec![]);
+            update_dims(abs.to_string_lossy().as_ref(), &mut sorted_items, &meta_types);
             sort_items(&mut sorted_items, &ordering);
             
         }
+        if meta_types.contains(&MetaType::DirSz) {
+            self.update_dir_sz(&abs, &mut sorted_items).await;
+            sort_items(&mut sorted_items, &ordering);
+        }
         let max_len_nm: Option<usize> = Some(update_max_len_nm(&sorted_items));
         let len_items = sorted_items.len();
-        let mut skip = skip_n.unwrap_or(0);
-        skip = cmp::min(skip, len_items);
-
-        let take = match take_n {
-            Some(n) => cmp::min(n, len_items - skip),
-            None =>  len_items - skip
-        };
-        let items_sliced: Vec<Item> = sorted_items.iter().skip(skip).take(take).cloned().collect();
+        let (skip, mut take, mut items_sliced) = slice_items(&sorted_items, skip_n, take_n);
+        if let Some(max) = params.max_nodes {
+            items_sliced.truncate(max);
+            take = items_sliced.len();
+        }
+        if params.depth > 1 {
+            let mut ancestors: HashSet<PathBuf> = abs.canonicalize().into_iter().collect();
+            let mut node_cnt = items_sliced.len();
+            fill_tree(&abs, &mut items_sliced, params, opt_filter.as_ref(), 1, &mut ancestors, &mut node_cnt);
+        }
 
         folder.skip_n = Some(skip);
         folder.take_n = Some(take);
@@ -198,6 +268,397 @@ impl Api {
         Ok(folder)
     }
 
+    /// `sz` of directory items from `cache_dir_sz`, `None` if not computed for the current mtime
+    async fn update_dir_sz(&self, path: &Path, items: &mut [Item]) {
+        for item in items.iter_mut().filter(|item| item.dir) {
+            let sub_path = path.join(&item.nm);
+            item.sz = match sub_path.metadata().and_then(|m| m.modified()) {
+                Ok(tm) => {
+                    let key = CacheDirSzKey { path: sub_path.to_string_lossy().into_owned(), tm };
+                    self.cache_dir_sz.get(&key).await.map(|dir_size| dir_size.sz)
+                }
+                Err(_) => None,
+            };
+        }
+    }
+
+    ///
+    /// run `job` on a blocking thread with progress reporting and `cancel_dir_size` support
+    ///
+    /// `on_progress` gets the running totals every `PROGRESS_MS`, the sizes of all folders
+    /// walked are cached unless `job` returns `None` (canceled).
+    async fn run_walk<F, J, R>(&self, abs: PathBuf, on_progress: F, job: J) -> Result<(Option<R>, DirSize), ApiError>
+    where
+        F: Fn(DirSize) + Send + 'static,
+        J: FnOnce(&SizeWalker, &Path) -> Option<R> + Send + 'static,
+        R: Send + 'static {
+        let path = abs.to_string_lossy().into_owned();
+        let stat = Arc::new(WalkStat::default());
+        let report = {
+            let stat = stat.clone();
+            let path = path.clone();
+            move || on_progress(stat.to_dir_size(&path))
+        };
+        let ((res, sizes), _) = {
+            let stat = stat.clone();
+            self.size_jobs.run(&path, report, move |cancel| {
+                let walker = SizeWalker::new(&stat, cancel);
+                let res = job(&walker, &abs);
+                (res, walker.sizes.into_inner().unwrap_or_default())
+            }).await?
+        };
+        if res.is_some() {
+            for (sub_path, tm, sub_size) in sizes {
+                let key = CacheDirSzKey { path: sub_path.to_string_lossy().into_owned(), tm };
+                self.cache_dir_sz.insert(key, sub_size).await;
+            }
+        }
+        Ok((res, stat.to_dir_size(&path)))
+    }
+
+    ///
+    /// total size of the folder tree at `path_str`
+    ///
+    /// The result and the totals of every sub folder are cached by path and mtime,
+    /// a canceled walk returns the partial totals.
+    pub async fn compute_dir_size<F>(&self, path_str: &str, on_progress: F) -> Result<DirSize, ApiError>
+    where
+        F: Fn(DirSize) + Send + 'static {
+        let abs = std::path::absolute(PathBuf::from(path_str))?;
+        let key = CacheDirSzKey { path: abs.to_string_lossy().into_owned(), tm: abs.metadata()?.modified()? };
+        if let Some(dir_size) = self.cache_dir_sz.get(&key).await {
+            return Ok(dir_size);
+        }
+        let (res, partial) = self.run_walk(abs, on_progress, |walker, path| walker.walk(path)).await?;
+        Ok(res.unwrap_or(DirSize {
+            canceled: Some(true),
+            ..partial
+        }))
+    }
+
+    ///
+    /// the `top_n` largest files and folders of every folder down to `depth`, for treemaps
+    ///
+    /// Shares progress, cancellation and the folder size cache with `compute_dir_size`.
+    pub async fn analyze_disk_usage<F>(&self, path_str: &str, top_n: usize, depth: usize, on_progress: F) -> Result<DiskUsage, ApiError>
+    where
+        F: Fn(DirSize) + Send + 'static {
+        let abs = std::path::absolute(PathBuf::from(path_str))?;
+        if !abs.is_dir() {
+            return Err(ApiError::Folder(String::from("Err Not Dir")));
+        }
+        let mut disk_usage = DiskUsage {
+            path: abs.to_string_lossy().into_owned(),
+            top_n,
+            depth,
+            ..DiskUsage::default()
+        };
+        let (res, _) = self.run_walk(abs, on_progress, move |walker, path| walker.walk_usage(path, top_n, depth)).await?;
+        match res {
+            Some(mut root) => {
+                let sz = root.sz;
+                update_pct(&mut root, sz, sz);
+                disk_usage.root = Some(root);
+            }
+            None => disk_usage.canceled = Some(true),
+        }
+        Ok(disk_usage)
+    }
+
+    /// stop a running `compute_dir_size`/`analyze_disk_usage`; returns `false` if none is running for the path
+    pub fn cancel_dir_size(&self, path_str: &str) -> Result<bool, ApiError> {
+        let path = std::path::absolute(PathBuf::from(path_str))?.to_string_lossy().into_owned();
+        self.size_jobs.cancel(&path)
+    }
+
+    ///
+    /// search the contents of the files under `root`
+    ///
+    /// `on_file` gets the matches of each file as soon as it is searched. Stopped by `cancel_grep`.
+    pub async fn grep<F>(&self, root: &str, pattern: &str, opt: &GrepOpt, on_file: F) -> Result<GrepSummary, ApiError>
+    where
+        F: Fn(GrepFile) + Send + Sync + 'static {
+        let abs = std::path::absolute(PathBuf::from(root))?;
+        if !abs.is_dir() {
+            return Err(ApiError::Folder(format!("Err Grep: not a folder `{}`", root)));
+        }
+        let matcher = Matcher::new(pattern, opt.regex.unwrap_or(false), opt.case_sensitive.unwrap_or(false))?;
+        let key = abs.to_string_lossy().into_owned();
+        let cancel = self.grep_jobs.start(&key)?;
+
+        let opt = opt.clone();
+        let summary = {
+            let cancel = cancel.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut grep = Grep::new(matcher, &cancel, &on_file);
+                grep.include = opt.include.unwrap_or_default();
+                grep.exclude = opt.exclude.unwrap_or_default();
+                grep.hidden = opt.hidden.unwrap_or(false);
+                grep.max_hits = opt.max_hits.unwrap_or(10_000);
+                grep.max_file_hits = opt.max_file_hits.unwrap_or(100);
+                grep.run(&abs)
+            }).await
+        };
+        self.grep_jobs.finish(&key, &cancel);
+        summary.map_err(|err| ApiError::Folder(err.to_string()))
+    }
+
+    /// stop a running `grep`; returns `false` if none is running for `root`
+    pub fn cancel_grep(&self, root: &str) -> Result<bool, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        self.grep_jobs.cancel(&key)
+    }
+
+    ///
+    /// find files and folders under `root` whose relative path matches `query`
+    ///
+    /// `on_hits` gets the hits of each folder as soon as it is listed, the result has the best
+    /// `max_results` of them ranked. Stopped by `cancel_find`.
+    pub async fn find<F>(&self, root: &str, query: &str, mode: FindMode, max_results: usize, on_hits: F) -> Result<FindResult, ApiError>
+    where
+        F: Fn(Vec<FindHit>) + Send + Sync + 'static {
+        let abs = std::path::absolute(PathBuf::from(root))?;
+        if !abs.is_dir() {
+            return Err(ApiError::Folder(format!("Err Find: not a folder `{}`", root)));
+        }
+        let key = abs.to_string_lossy().into_owned();
+        let cancel = self.find_jobs.start(&key)?;
+
+        let query = query.to_string();
+        let hits = {
+            let cancel = cancel.clone();
+            tokio::task::spawn_blocking(move || {
+                Finder::new(&query, mode, &cancel, &on_hits).map(|mut finder| {
+                    finder.max_results = max_results;
+                    finder.run(&abs)
+                })
+            }).await
+        };
+        self.find_jobs.finish(&key, &cancel);
+        let (hits, total) = hits.map_err(|err| ApiError::Folder(err.to_string()))??;
+        Ok(FindResult {
+            root: key,
+            hits,
+            total,
+            canceled: cancel.load(Ordering::Relaxed).then_some(true),
+        })
+    }
+
+    /// stop a running `find`; returns `false` if none is running for `root`
+    pub fn cancel_find(&self, root: &str) -> Result<bool, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        self.find_jobs.cancel(&key)
+    }
+
+    ///
+    /// build or refresh the file name index of `root`, saved under `<cache_dir>/name-index`
+    ///
+    /// Meant for the paths of `get_disks` and `get_home_dir`. Only folders whose mtime changed since
+    /// the last update are read again. `on_progress` gets the running totals every `PROGRESS_MS`.
+    /// Stopped by `cancel_name_index`, the previous index is kept then.
+    pub async fn update_name_index<F>(&self, root: &str, on_progress: F) -> Result<NameIndexInfo, ApiError>
+    where
+        F: Fn(NameIndexInfo) + Send + 'static {
+        let abs = std::path::absolute(PathBuf::from(root))?;
+        if !abs.is_dir() {
+            return Err(ApiError::Folder(format!("Err NameIndex: not a folder `{}`", root)));
+        }
+        let key = abs.to_string_lossy().into_owned();
+        let prev = self.name_index(&key).await;
+        let stat = Arc::new(IndexStat::default());
+        let report = {
+            let stat = stat.clone();
+            let key = key.clone();
+            move || on_progress(stat.to_info(&key))
+        };
+        let index_dir = self.cache_dir.join("name-index");
+        let (update_res, _) = {
+            let stat = stat.clone();
+            self.index_jobs.run(&key, report, move |cancel| {
+                let idx = NameIndex::update(&abs, prev.as_deref(), &stat, cancel)?;
+                Some(idx.save(&index_dir).map(|_| idx))
+            }).await?
+        };
+        match update_res {
+            Some(idx) => {
+                let idx = idx?;
+                let info = NameIndexInfo {
+                    read_dirs: Some(stat.read_dirs.load(Ordering::Relaxed)),
+                    ..idx.info()
+                };
+                self.cache_name_index.insert(key, Arc::new(idx)).await;
+                Ok(info)
+            }
+            None => Ok(NameIndexInfo {
+                canceled: Some(true),
+                ..stat.to_info(&key)
+            }),
+        }
+    }
+
+    /// stop a running `update_name_index`; returns `false` if none is running for `root`
+    pub fn cancel_name_index(&self, root: &str) -> Result<bool, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        self.index_jobs.cancel(&key)
+    }
+
+    /// the name index of `root` as last updated, `None` if it was never built
+    pub async fn get_name_index_info(&self, root: &str) -> Result<Option<NameIndexInfo>, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        Ok(self.name_index(&key).await.map(|idx| idx.info()))
+    }
+
+    ///
+    /// search the file name index of `root` without walking it
+    ///
+    /// Same matching and ranking as `find`, over the state of the last `update_name_index`.
+    pub async fn search_name_index(&self, root: &str, query: &str, mode: FindMode, max_results: usize) -> Result<FindResult, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        let query = Query::new(query, mode)?;
+        let Some(idx) = self.name_index(&key).await else {
+            return Err(ApiError::Folder(format!("Err NameIndex: no index of `{}`", root)));
+        };
+        let (hits, total) = tokio::task::spawn_blocking(move || idx.search(&query, max_results)).await
+            .map_err(|err| ApiError::Folder(err.to_string()))?;
+        Ok(FindResult {
+            root: key,
+            hits,
+            total,
+            canceled: None,
+        })
+    }
+
+    /// from memory, else from `<cache_dir>/name-index`
+    async fn name_index(&self, key: &str) -> Option<Arc<NameIndex>> {
+        if let Some(idx) = self.cache_name_index.get(key).await {
+            return Some(idx);
+        }
+        let index_dir = self.cache_dir.join("name-index");
+        let root = key.to_string();
+        let idx = Arc::new(tokio::task::spawn_blocking(move || NameIndex::load(&root, &index_dir)).await.ok()??);
+        self.cache_name_index.insert(key.to_string(), idx.clone()).await;
+        Some(idx)
+    }
+
+    ///
+    /// build or refresh the full-text index of `root`, saved under `<cache_dir>/text-index`
+    ///
+    /// Opt-in per folder. Only files whose size or mtime changed are read again, `opt` defaults to
+    /// the options of the last update. `on_progress` gets the running totals every `PROGRESS_MS`.
+    /// Stopped by `cancel_text_index`, the previous index is kept then.
+    pub async fn update_text_index<F>(&self, root: &str, opt: Option<TextIndexOpt>, on_progress: F) -> Result<TextIndexInfo, ApiError>
+    where
+        F: Fn(TextIndexInfo) + Send + 'static {
+        let abs = std::path::absolute(PathBuf::from(root))?;
+        if !abs.is_dir() {
+            return Err(ApiError::Folder(format!("Err TextIndex: not a folder `{}`", root)));
+        }
+        let key = abs.to_string_lossy().into_owned();
+        let prev = self.text_index(&key).await;
+        let opt = opt.or_else(|| prev.as_ref().map(|prev| prev.opt.clone())).unwrap_or_default();
+        let stat = Arc::new(TextIndexStat::default());
+        let report = {
+            let stat = stat.clone();
+            let key = key.clone();
+            move || on_progress(stat.to_info(&key))
+        };
+        let index_dir = self.cache_dir.join("text-index");
+        let (update_res, _) = {
+            let stat = stat.clone();
+            self.text_index_jobs.run(&key, report, move |cancel| {
+                let idx = TextIndex::update(&abs, prev.as_deref(), &opt, &stat, cancel)?;
+                Some(idx.save(&index_dir).map(|_| idx))
+            }).await?
+        };
+        match update_res {
+            Some(idx) => {
+                let idx = idx?;
+                let info = TextIndexInfo {
+                    read_docs: Some(stat.read_docs.load(Ordering::Relaxed)),
+                    ..idx.info()
+                };
+                self.cache_text_index.insert(key, Arc::new(idx)).await;
+                Ok(info)
+            }
+            None => Ok(TextIndexInfo {
+                canceled: Some(true),
+                ..stat.to_info(&key)
+            }),
+        }
+    }
+
+    /// stop a running `update_text_index`; returns `false` if none is running for `root`
+    pub fn cancel_text_index(&self, root: &str) -> Result<bool, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        self.text_index_jobs.cancel(&key)
+    }
+
+    /// delete the full-text index of `root`; returns `false` if there was none
+    pub async fn remove_text_index(&self, root: &str) -> Result<bool, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        self.cache_text_index.invalidate(&key).await;
+        let index_path = index_file(&self.cache_dir.join("text-index"), &key);
+        match tokio::fs::remove_file(&index_path).await {
+            Ok(()) => Ok(true),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// the full-text index of `root` as last updated, `None` if it was never built
+    pub async fn get_text_index_info(&self, root: &str) -> Result<Option<TextIndexInfo>, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        Ok(self.text_index(&key).await.map(|idx| idx.info()))
+    }
+
+    ///
+    /// documents under `root` containing every word of `query`, ranked, with highlighted snippets
+    ///
+    /// Query words also match longer words they start. The snippets are read from the files as they are now.
+    pub async fn search_text_index(&self, root: &str, query: &str, max_results: usize, max_snippets: usize) -> Result<TextIndexResult, ApiError> {
+        let key = std::path::absolute(PathBuf::from(root))?.to_string_lossy().into_owned();
+        let matcher = word_matcher(query)?;
+        let Some(idx) = self.text_index(&key).await else {
+            return Err(ApiError::Folder(format!("Err TextIndex: no index of `{}`", root)));
+        };
+        let query = query.to_string();
+        let root_path = PathBuf::from(&key);
+        let (hits, total) = tokio::task::spawn_blocking(move || -> Result<(Vec<TextIndexHit>, usize), ApiError> {
+            let (ranked, total) = idx.search(&query, max_results)?;
+            let hits = ranked.into_iter().map(|(doc, score)| {
+                let path = root_path.join(&doc.rel);
+                let hits = std::fs::File::open(&path).ok()
+                    .and_then(|file| search_lines(std::io::BufReader::new(file), doc.encoding(), &matcher, max_snippets).ok())
+                    .map(|(hits, _)| hits)
+                    .unwrap_or_default();
+                TextIndexHit {
+                    path: path.to_string_lossy().into_owned(),
+                    rel: doc.rel.clone(),
+                    score,
+                    hits,
+                }
+            }).collect();
+            Ok((hits, total))
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))??;
+        Ok(TextIndexResult {
+            root: key,
+            hits,
+            total,
+        })
+    }
+
+    /// from memory, else from `<cache_dir>/text-index`
+    async fn text_index(&self, key: &str) -> Option<Arc<TextIndex>> {
+        if let Some(idx) = self.cache_text_index.get(key).await {
+            return Some(idx);
+        }
+        let index_dir = self.cache_dir.join("text-index");
+        let root = key.to_string();
+        let idx = Arc::new(tokio::task::spawn_blocking(move || TextIndex::load(&root, &index_dir)).await.ok()??);
+        self.cache_text_index.insert(key.to_string(), idx.clone()).await;
+        Some(idx)
+    }
+
     pub async fn set_state(&self, key: String, opt_val: Option<String>) -> Result<Option<String>, ApiError> {
         match opt_val.clone() {
             None => {
@@ -223,7 +684,13 @@ impl Api {
         }
     }
 
-    pub async fn read_txt(&self, path_str: &str) -> Result<TextContent, ApiError> {
+    ///
+    /// read a text file up to 5MB
+    ///
+    /// # arg
+    /// - encoding: label of the encoding to decode with instead of the detected one, e.g. `"cp949"`
+    /// - lossy: return the text with U+FFFD for malformed sequences instead of `None`
+    pub async fn read_txt(&self, path_str: &str, encoding: Option<&str>, lossy: bool) -> Result<TextContent, ApiError> {
         let path = PathBuf::from(path_str);
 
         let mut file = tokio::fs::File::open(&path).await?;
@@ -245,8 +712,6 @@ impl Api {
         //     }
         // }
 
-        println!("mime_type: {}", mime_type);
-
         // application/octet-stream  인경우 기본적으로 안보이게 하나 file_size가 5M 미만인경우는 열기시도
         let sz = path.metadata()?.len();
         
@@ -256,19 +721,37 @@ impl Api {
                 path: path_str.to_string(),
                 mimetype: mime_type,
                 enc: None,
-                text: None
+                text: None,
+                ..TextContent::default()
             })
         } else {
             file = tokio::fs::File::open(&path).await?;
             let mut buffer = Vec::new();
             file.read_to_end(&mut buffer).await?;
 
-            let mut detector = EncodingDetector::new();
-            detector.feed(&buffer, true);
-            let encoding: &Encoding = detector.guess(None, true);
+            let bom = Encoding::for_bom(&buffer);
+            // an override needs no detection, else the start of the file is enough
+            let detect = || detect_candidates(&buffer[..buffer.len().min(DETECT_SZ)], buffer.len() <= DETECT_SZ);
+            let (encoding, confident, candidates): (&Encoding, _, _) = match encoding {
+                Some(label) => {
+                    let encoding = Encoding::for_label(label.as_bytes())
+                        .ok_or_else(|| ApiError::Folder(format!("Err Encoding: unknown `{}`", label)))?;
+                    (encoding, None, None)
+                }
+                None => {
+                    let (guess, confident, candidates) = detect();
+                    (guess, Some(confident), Some(candidates))
+                }
+            };
 
-            let (text, _, had_errors) = encoding.decode(&buffer);
-            let opt_text = if had_errors {
+            let (text, had_errors) = encoding.decode_with_bom_removal(&buffer);
+            let err_cnt = if had_errors {
+                let bom_len = bom.filter(|(bom_encoding, _)| *bom_encoding == encoding).map_or(0, |(_, len)| len);
+                count_errors(encoding, &buffer[bom_len..], true)
+            } else {
+                0
+            };
+            let opt_text = if had_errors && !lossy {
                 None
             } else {
                 Some(text.into_owned())
@@ -278,11 +761,406 @@ impl Api {
                 path: path_str.to_string(),
                 mimetype: mime_type,
                 enc: Some(encoding.name().to_string()),
-                text: opt_text
+                bom: Some(bom.is_some()),
+                confident,
+                err_cnt: Some(err_cnt),
+                candidates,
+                line_ending: opt_text.as_deref().and_then(detect_line_ending),
+                stats: opt_text.as_deref().map(text_stats),
+                tm: Some(path.metadata()?.modified()?.to_ns().to_string()),
+                text: opt_text,
             })
         }
     }
 
+    ///
+    /// save a text file
+    ///
+    /// # arg
+    /// - text: converted to `line_ending` if given
+    /// - encoding: label of the encoding, e.g. `enc` of `read_txt`
+    /// - expected_mtime: `tm` of `read_txt`; fails if the file changed since, or was deleted
+    ///
+    /// The byte order mark of the existing file is kept. Written to a temp file in the folder of the
+    /// real file, a symlink is followed and stays a symlink, which is then renamed over the file.
+    /// Returns the new mtime like `tm`.
+    pub async fn write_text(&self, path_str: &str, text: &str, encoding: &str, line_ending: Option<LineEnding>, expected_mtime: Option<&str>) -> Result<String, ApiError> {
+        let path = PathBuf::from(path_str);
+        let path = tokio::fs::canonicalize(&path).await.unwrap_or(path);
+        let encoding = Encoding::for_label(encoding.as_bytes())
+            .ok_or_else(|| ApiError::Folder(format!("Err Encoding: unknown `{}`", encoding)))?;
+        let metadata = tokio::fs::metadata(&path).await.ok();
+        if let Some(expected) = expected_mtime {
+            let expected: u128 = expected.parse()
+                .map_err(|_| ApiError::Folder(format!("Err Mtime: `{}`", expected)))?;
+            let tm = match metadata.as_ref() {
+                Some(metadata) => metadata.modified()?.to_ns(),
+                None => return Err(ApiError::Folder(format!("Err Changed: `{}` was deleted", path_str))),
+            };
+            if tm != expected {
+                return Err(ApiError::Folder(format!("Err Changed: `{}` was modified", path_str)));
+            }
+        }
+        let bom = match metadata {
+            Some(_) => {
+                let mut head = [0u8; 3];
+                let mut file = tokio::fs::File::open(&path).await?;
+                let n = file.read(&mut head).await?;
+                Encoding::for_bom(&head[..n]).is_some()
+            }
+            None => false,
+        };
+        let bytes = match line_ending {
+            Some(line_ending) => encode_text(&convert_line_ending(text, line_ending), encoding, bom)?,
+            None => encode_text(text, encoding, bom)?,
+        };
+
+        if path.file_name().is_none() {
+            return Err(ApiError::Folder(format!("Err Path: `{}`", path_str)));
+        }
+        let tmp_path = path.tmp_sibling();
+        let write_res = async {
+            let mut file = tokio::fs::File::create(&tmp_path).await?;
+            file.write_all(&bytes).await?;
+            file.sync_all().await?;
+            if let Some(metadata) = metadata.as_ref() {
+                tokio::fs::set_permissions(&tmp_path, metadata.permissions()).await?;
+            }
+            tokio::fs::rename(&tmp_path, &path).await
+        }.await;
+        if let Err(err) = write_res {
+            let _ = tokio::fs::remove_file(&tmp_path).await;
+            return Err(err.into());
+        }
+        Ok(tokio::fs::metadata(&path).await?.modified()?.to_ns().to_string())
+    }
+
+    /// encoding `read_text_range` detected, else detected from the start of the file
+    async fn text_encoding(&self, path: &Path) -> Result<&'static Encoding, ApiError> {
+        match self.cache_line_index.get(path.to_string_lossy().as_ref()).await {
+            Some(idx) => Ok(idx.encoding()),
+            None => detect_encoding(path),
+        }
+    }
+
+    ///
+    /// find `pattern` in a text file of any size
+    ///
+    /// # arg
+    /// - regex: `pattern` is a regex, else a literal
+    /// - max_hits: stop after this many matches
+    ///
+    /// Lines are matched one at a time, a match can not span lines.
+    pub async fn search_in_file(&self, path_str: &str, pattern: &str, regex: bool, case_sensitive: bool, max_hits: usize) -> Result<SearchResult, ApiError> {
+        let path = PathBuf::from(path_str);
+        let matcher = Matcher::new(pattern, regex, case_sensitive)?;
+        let encoding = self.text_encoding(&path).await?;
+        let (hits, truncated) = tokio::task::spawn_blocking(move || {
+            let reader = std::io::BufReader::new(std::fs::File::open(&path)?);
+            search_lines(reader, encoding, &matcher, max_hits)
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))??;
+        Ok(SearchResult {
+            path: path_str.to_string(),
+            enc: encoding.name().to_string(),
+            hits,
+            truncated,
+        })
+    }
+
+    ///
+    /// read a page of a text file of any size
+    ///
+    /// The page starts at line `line_start`, else at byte `offset` (moved forward to a character start),
+    /// else at the top. It holds up to `line_cnt` lines and `max_bytes` bytes (default 1MB).
+    /// The line index and the detected encoding are kept under `cache_dir` and reused by later pages.
+    pub async fn read_text_range(&self, path_str: &str, offset: Option<u64>, line_start: Option<u64>, line_cnt: Option<u64>, max_bytes: Option<usize>) -> Result<TextPage, ApiError> {
+        let path = PathBuf::from(path_str);
+        let index_dir = self.cache_dir.join("line-index");
+        let prev = self.cache_line_index.get(path_str).await;
+        let (idx, page) = tokio::task::spawn_blocking(move || -> Result<(LineIndex, TextPage), ApiError> {
+            let idx = LineIndex::load_or_build(&path, &index_dir, prev)?;
+            let mut file = std::fs::File::open(&path)?;
+            let (offset, line_no) = match (line_start, offset) {
+                (Some(line_no), _) => (idx.line_offset(&mut file, line_no)?, line_no),
+                (None, Some(offset)) => {
+                    let offset = snap_offset(&idx, &mut file, offset)?;
+                    (offset, idx.line_at(&mut file, offset)?.0)
+                }
+                (None, None) => (0, 0),
+            };
+            let page = read_page(&idx, &mut file, offset, line_no, line_cnt.unwrap_or(u64::MAX), max_bytes.unwrap_or(DEFAULT_PAGE_BYTES))?;
+            Ok((idx, page))
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))??;
+        self.cache_line_index.insert(path_str.to_string(), idx).await;
+        Ok(page)
+    }
+
+    ///
+    /// hex dump of a part of any file, for files `read_txt` can't show
+    ///
+    /// Rows of `bytes_per_row` (default 16, at most 64) bytes from `offset` moved back to its row start,
+    /// `length` bytes in all (default 4KB, at most 256KB).
+    pub async fn read_hex(&self, path_str: &str, offset: Option<u64>, length: Option<usize>, bytes_per_row: Option<usize>) -> Result<HexPage, ApiError> {
+        let path = PathBuf::from(path_str);
+        let page = tokio::task::spawn_blocking(move || -> Result<HexPage, ApiError> {
+            let mut file = std::fs::File::open(&path)?;
+            let sz = file.metadata()?.len();
+            let page = read_hex(&mut file, sz, offset.unwrap_or(0), length.unwrap_or(DEFAULT_HEX_LEN), bytes_per_row.unwrap_or(DEFAULT_BYTES_PER_ROW))?;
+            Ok(HexPage {
+                path: path.to_string_lossy().into_owned(),
+                ..page
+            })
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))??;
+        Ok(page)
+    }
+
+    ///
+    /// offsets of a byte pattern in a file of any size
+    ///
+    /// `pattern` is hex digits (`ff d8 ff`) if `hex`, else text matched as UTF-8 bytes.
+    /// Searches from `start`, so the next hits come from the last offset + 1.
+    pub async fn find_bytes(&self, path_str: &str, pattern: &str, hex: bool, start: Option<u64>, max_hits: usize) -> Result<BytesResult, ApiError> {
+        let path = PathBuf::from(path_str);
+        let pattern = parse_pattern(pattern, hex)?;
+        let result = tokio::task::spawn_blocking(move || -> Result<BytesResult, ApiError> {
+            let mut file = std::fs::File::open(&path)?;
+            let (offsets, truncated) = find_bytes(&mut file, &pattern, start.unwrap_or(0), max_hits)?;
+            Ok(BytesResult {
+                path: path.to_string_lossy().into_owned(),
+                len: pattern.len(),
+                offsets,
+                truncated,
+            })
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))??;
+        Ok(result)
+    }
+
+    ///
+    /// raw bytes of a file from `offset`, for images and audio slices
+    ///
+    /// `length` defaults to the rest of the file. Ranges over `ApiConfig.max_read_bytes` are an error,
+    /// ranges of `ApiConfig.mmap_min_bytes` or more are read through a memory map.
+    pub async fn read_bytes(&self, path_str: &str, offset: Option<u64>, length: Option<u64>) -> Result<Vec<u8>, ApiError> {
+        let config = self.get_config()?;
+        let path = PathBuf::from(path_str);
+        let offset = offset.unwrap_or(0);
+        tokio::task::spawn_blocking(move || -> Result<Vec<u8>, ApiError> {
+            let mut file = std::fs::File::open(&path)?;
+            let sz = file.metadata()?.len();
+            let len = length.unwrap_or(u64::MAX).min(sz.saturating_sub(offset));
+            if len > config.max_read_bytes {
+                return Err(ApiError::Folder(format!("Err ReadBytes: {} bytes over the limit of {}", len, config.max_read_bytes)));
+            }
+            let mmap = config.mmap_min_bytes > 0 && len >= config.mmap_min_bytes;
+            read_range(&mut file, offset, len as usize, mmap)
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))?
+    }
+
+    ///
+    /// image scaled down to fit `max_size`, turned upright by its EXIF orientation
+    ///
+    /// Thumbnails are kept in the freedesktop cache (`<thumb_dir>/normal|large|x-large|xx-large`,
+    /// named by the md5 of the file URI) and made again when the mtime of the file changes.
+    pub async fn get_thumbnail(&self, path_str: &str, max_size: u32, format: ThumbFormat) -> Result<Vec<u8>, ApiError> {
+        let path = std::path::absolute(PathBuf::from(path_str))?;
+        let thumb_dir = self.thumb_dir.clone();
+        let max_size = max_size.clamp(1, MAX_THUMB_SIZE);
+        tokio::task::spawn_blocking(move || -> Result<Vec<u8>, ApiError> {
+            let mtime = std::fs::metadata(&path)?.modified()?.to_sec();
+            let uri = file_uri(&path);
+            let (size_dir, size) = bucket(max_size);
+            let thumb = thumb_path(&thumb_dir, &uri, size_dir);
+            let png = match load_cached(&thumb, &uri, mtime) {
+                Some(png) => png,
+                None => save_thumbnail(&make_thumbnail(&path, size)?, &thumb, &uri, mtime)?,
+            };
+            if size == max_size && format == ThumbFormat::Png {
+                return Ok(png);
+            }
+            let img = image::load_from_memory(&png)
+                .map_err(|err| ApiError::Folder(format!("Err Thumbnail: {}", err)))?;
+            encode(&fit(img, max_size), format)
+        }).await.map_err(|err| ApiError::Folder(err.to_string()))?
+    }
+
+    /// size, color type, frames and EXIF of an image, from its headers
+    pub async fn read_image_info(&self, path_str: &str) -> Result<ImageInfo, ApiError> {
+        let path = PathBuf::from(path_str);
+        tokio::task::spawn_blocking(move || read_image_info(&path))
+            .await.map_err(|err| ApiError::Folder(err.to_string()))?
+    }
+
+    ///
+    /// serve the files under `roots` over http on localhost, for media seeking
+    ///
+    /// The server is started by the first call and shared, each call gets its own token that only
+    /// allows its `roots`. Requests go to `url` + the percent-encoded path and may use `Range`.
+    pub async fn start_media_server(&self, roots: &[String]) -> Result<MediaServerInfo, ApiError> {
+        let running = self.media_server.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .is_some();
+        if !running {
+            let server = MediaServer::start().await?;
+            let mut media_server = self.media_server.lock().map_err(|err| ApiError::Folder(err.to_string()))?;
+            match media_server.as_ref() {
+                // started meanwhile by another call
+                Some(_) => server.stop(),
+                None => *media_server = Some(server),
+            }
+        }
+        let media_server = self.media_server.lock().map_err(|err| ApiError::Folder(err.to_string()))?;
+        let Some(server) = media_server.as_ref() else {
+            return Err(ApiError::Folder(String::from("Err MediaServer: not running")));
+        };
+        let token = server.add_token(roots)?;
+        Ok(MediaServerInfo {
+            port: server.port,
+            url: format!("http://127.0.0.1:{}/{}/", server.port, token),
+            token,
+        })
+    }
+
+    /// revoke a token of `start_media_server`, the server stops with the last one; `false` if unknown
+    pub fn stop_media_server(&self, token: &str) -> Result<bool, ApiError> {
+        let mut media_server = self.media_server.lock().map_err(|err| ApiError::Folder(err.to_string()))?;
+        let Some(server) = media_server.as_ref() else {
+            return Ok(false);
+        };
+        let removed = server.remove_token(token)?;
+        if server.token_cnt() == 0 {
+            server.stop();
+            *media_server = None;
+        }
+        Ok(removed)
+    }
+
+    pub fn get_config(&self) -> Result<ApiConfig, ApiError> {
+        Ok(self.config.lock().map_err(|err| ApiError::Folder(err.to_string()))?.clone())
+    }
+
+    pub fn set_config(&self, config: ApiConfig) -> Result<ApiConfig, ApiError> {
+        *self.config.lock().map_err(|err| ApiError::Folder(err.to_string()))? = config.clone();
+        Ok(config)
+    }
+
+    ///
+    /// watch a folder and call `on_events` with debounced changes
+    ///
+    /// `cache_folder` entries of the changed folders are invalidated before `on_events` is called.
+    /// Returns the id for `unwatch`.
+    pub fn watch<F>(&self, path_str: &str, recursive: bool, on_events: F) -> Result<u32, ApiError>
+    where
+        F: Fn(Vec<WatchEvent>) + Send + 'static {
+        let abs = std::path::absolute(PathBuf::from(path_str))?;
+        let cache_folder = self.cache_folder.clone();
+        let mut debouncer = new_debouncer(Duration::from_millis(WATCH_DEBOUNCE_MS), None, move |res: DebounceEventResult| {
+            // watcher errors are dropped, it keeps going
+            let Ok(events) = res else {
+                return;
+            };
+            let watch_events = to_watch_events(&events);
+            if watch_events.is_empty() {
+                return;
+            }
+            let folders: HashSet<String> = watch_events.iter()
+                .flat_map(affected_folders)
+                .map(|p| p.to_string_lossy().into_owned())
+                .collect();
+            // only fails if the cache was built without invalidation closures
+            let _ = cache_folder.invalidate_entries_if(move |k, _| folders.contains(&k.path));
+            on_events(watch_events);
+        })?;
+        let mode = if recursive { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+        debouncer.watch(&abs, mode)?;
+
+        let id = self.watch_id.fetch_add(1, Ordering::Relaxed);
+        self.watchers.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .insert(id, debouncer);
+        Ok(id)
+    }
+
+    ///
+    /// poll the mounted disks every `DISK_POLL_MS` and call `on_events` when disks are added or removed
+    ///
+    /// Returns the id for `unwatch`.
+    pub fn watch_disks<F>(&self, on_events: F) -> Result<u32, ApiError>
+    where
+        F: Fn(Vec<DiskEvent>) + Send + 'static {
+        let stop = Arc::new(AtomicBool::new(false));
+        let id = self.watch_id.fetch_add(1, Ordering::Relaxed);
+        self.pollers.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .insert(id, stop.clone());
+
+        std::thread::spawn(move || {
+            let mut disks = Disks::new_with_refreshed_list();
+            let mut old: Vec<DiskInfo> = disks.iter().map(to_disk_info).collect();
+            while !stop.load(Ordering::Relaxed) {
+                std::thread::sleep(Duration::from_millis(DISK_POLL_MS));
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                disks.refresh(true);
+                let new: Vec<DiskInfo> = disks.iter().map(to_disk_info).collect();
+                let events = diff_disks(&old, &new);
+                if !events.is_empty() {
+                    on_events(events);
+                }
+                old = new;
+            }
+        });
+        Ok(id)
+    }
+
+    ///
+    /// follow a file like `tail -f`, `on_events` gets the lines appended from now on
+    ///
+    /// Lines are decoded with `encoding` (a label, e.g. the `enc` the viewer shows), else with the one
+    /// `read_text_range` detected, else it is detected from the file like `read_txt` does.
+    /// Returns the id for `unwatch`.
+    pub async fn tail<F>(&self, path_str: &str, encoding: Option<&str>, on_events: F) -> Result<u32, ApiError>
+    where
+        F: Fn(Vec<TailEvent>) + Send + 'static {
+        let encoding = match encoding {
+            Some(label) => Some(Encoding::for_label(label.as_bytes())
+                .ok_or_else(|| ApiError::Folder(format!("Err Encoding: unknown `{}`", label)))?),
+            None => self.cache_line_index.get(path_str).await.map(|idx| idx.encoding()),
+        };
+        let mut tailer = Tailer::new(Path::new(path_str), encoding)?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let id = self.watch_id.fetch_add(1, Ordering::Relaxed);
+        self.pollers.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .insert(id, stop.clone());
+
+        std::thread::spawn(move || {
+            while !stop.load(Ordering::Relaxed) {
+                match tailer.poll() {
+                    Ok(events) if !events.is_empty() => on_events(events),
+                    // nothing new, or the file is gone between a rotation and its replacement
+                    _ => {}
+                }
+                std::thread::sleep(Duration::from_millis(TAIL_POLL_MS));
+            }
+        });
+        Ok(id)
+    }
+
+    /// stop a `watch`, `watch_disks` or `tail`; returns `false` if `id` is unknown
+    pub fn unwatch(&self, id: u32) -> Result<bool, ApiError> {
+        let debouncer = self.watchers.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .remove(&id);
+        let poller = self.pollers.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .remove(&id);
+        if let Some(stop) = poller.as_ref() {
+            stop.store(true, Ordering::Relaxed);
+        }
+        Ok(debouncer.is_some() || poller.is_some())
+    }
+
     pub async fn get_home_dir(&self) -> Result<HashMap<HomeType, String>, ApiError> {
         Ok([
             (HomeType::RootDir, Some(std::path::absolute(PathBuf::from("/"))?)),
@@ -311,15 +1189,37 @@ impl Api {
         let disks = Disks::new_with_refreshed_list();
         let mut ret: Vec<DiskInfo> = vec![];
         for disk in &disks {
-            let disk_info = DiskInfo {
-                path: disk.mount_point().to_string_lossy().into_owned(),
-            };
-            ret.push(disk_info);
+            ret.push(to_disk_info(disk));
         }
         Ok(ret)
     }
 }
 
+fn to_disk_info(disk: &Disk) -> DiskInfo {
+    let path = disk.mount_point().to_string_lossy().into_owned();
+    let nm = disk.name().to_string_lossy().into_owned();
+    let display_nm = if nm.is_empty() || nm == path {
+        path.clone()
+    } else {
+        format!("{} ({})", nm, path)
+    };
+    DiskInfo {
+        path,
+        nm,
+        display_nm,
+        fs: disk.file_system().to_string_lossy().into_owned(),
+        kind: match disk.kind() {
+            sysinfo::DiskKind::SSD => DiskKind::Ssd,
+            sysinfo::DiskKind::HDD => DiskKind::Hdd,
+            sysinfo::DiskKind::Unknown(_) => DiskKind::Unknown,
+        },
+        total: disk.total_space(),
+        available: disk.available_space(),
+        removable: disk.is_removable(),
+        read_only: disk.is_read_only(),
+    }
+}
+
 
 
 
@@ -327,6 +1227,8 @@ impl Api {
 mod tests {
     // use crate::{models};
     use super::*;
+    use std::io::Write;
+    use crate::models::{WatchKind, MetaType, TailKind, OrdItem, OrderBy, OrderAsc};
 
 
     #[tokio::test]
@@ -334,6 +1236,7 @@ mod tests {
 
     }
 
+    #[cfg(windows)]
     #[tokio::test]
     async fn test_base() {
         let api = Api::default();
@@ -352,6 +1255,7 @@ mod tests {
         assert_eq!(api.get_folder(&params).await.unwrap().base_nm, "C:");
     }
 
+    #[cfg(windows)]
     #[tokio::test]
     async fn test_permissions() {
         let api = Api::default();
@@ -366,6 +1270,7 @@ mod tests {
     }
 
 
+    #[cfg(windows)]
     #[tokio::test]
     async fn test_dir() {
         let api = Api::default();
@@ -405,6 +1310,294 @@ mod tests {
         //     },
         // };
     }
+    #[tokio::test]
+    async fn test_get_folder_depth() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-depth");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("a").join("b").join("c")).unwrap();
+        std::fs::write(base_dir.join("a").join("f.txt"), b"x").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(&base_dir, base_dir.join("a").join("loop")).unwrap();
+
+        let params = Params {
+            path_str: base_dir.to_string_lossy().to_string(),
+            take_n: None,
+            depth: 3,
+            ..Params::default()
+        };
+        let folder = api.get_folder(&params).await.unwrap();
+        let a = &folder.item.items.unwrap()[0];
+        assert_eq!(a.nm, "a");
+        let a_items = a.items.as_ref().unwrap();
+        let b = a_items.iter().find(|item| item.nm == "b").unwrap();
+        assert_eq!(b.items.as_ref().unwrap()[0].nm, "c");
+        assert!(b.items.as_ref().unwrap()[0].items.is_none());
+        #[cfg(unix)]
+        assert!(a_items.iter().find(|item| item.nm == "loop").unwrap().items.is_none());
+
+        let params = Params {
+            max_nodes: Some(2),
+            ..params
+        };
+        let folder = api.get_folder(&params).await.unwrap();
+        let a = &folder.item.items.unwrap()[0];
+        assert_eq!(a.items.as_ref().unwrap().len(), 1);
+
+        // the next page starts after what was returned
+        let params = Params {
+            path_str: base_dir.join("a").to_string_lossy().to_string(),
+            max_nodes: Some(1),
+            ..params
+        };
+        let folder = api.get_folder(&params).await.unwrap();
+        assert_eq!((folder.skip_n, folder.take_n, folder.cnt), (Some(0), Some(1), Some(1)));
+        assert!(folder.tot.unwrap() > 1);
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_folder_filter() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-filter");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("img")).unwrap();
+        std::fs::write(base_dir.join("a.jpg"), vec![0u8; 2048]).unwrap();
+        std::fs::write(base_dir.join("b.jpg"), b"x").unwrap();
+        std::fs::write(base_dir.join("c.txt"), vec![0u8; 2048]).unwrap();
+
+        let params = Params {
+            path_str: base_dir.to_string_lossy().to_string(),
+            meta_types: vec![MetaType::Ext],
+            filter: Some(String::from("ext:jpg size>1K")),
+            cache_nm: Some(String::from("filter")),
+            ..Params::default()
+        };
+        let folder = api.get_folder(&params).await.unwrap();
+        assert_eq!(folder.tot, Some(1));
+        assert_eq!(folder.cnt, Some(1));
+        let items = folder.item.items.unwrap();
+        assert_eq!(items[0].nm, "a.jpg");
+        assert!(items[0].sz.is_none());
+
+        let params = Params {
+            filter: Some(String::from("dir:false")),
+            ..params
+        };
+        assert_eq!(api.get_folder(&params).await.unwrap().tot, Some(3));
+        assert!(api.get_folder(&Params { filter: Some(String::from("size>x")), ..params.clone() }).await.is_err());
+
+        // a tree keeps the folders so the filter reaches the files below them
+        std::fs::write(base_dir.join("img/d.jpg"), b"x").unwrap();
+        std::fs::write(base_dir.join("img/e.png"), b"x").unwrap();
+        let params = Params {
+            filter: Some(String::from("ext:jpg")),
+            depth: 2,
+            cache_nm: None,
+            ..params
+        };
+        let items = api.get_folder(&params).await.unwrap().item.items.unwrap();
+        let nms: Vec<&str> = items.iter().map(|item| item.nm.as_str()).collect();
+        assert_eq!(nms, vec!["img", "a.jpg", "b.jpg"]);
+        let sub_nms: Vec<&str> = items[0].items.as_ref().unwrap().iter().map(|item| item.nm.as_str()).collect();
+        assert_eq!(sub_nms, vec!["d.jpg"]);
+
+        // a flat listing and a tree with the same filter don't share their cached listing
+        for depth in [1, 2, 1, 2] {
+            let params = Params {
+                depth,
+                cache_nm: Some(String::from("tree")),
+                ..params.clone()
+            };
+            let items = api.get_folder(&params).await.unwrap().item.items.unwrap();
+            assert_eq!(items.iter().any(|item| item.nm == "img"), depth > 1);
+        }
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-watch");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let params = Params {
+            path_str: base_dir.to_string_lossy().to_string(),
+            cache_nm: Some(String::from("watch")),
+            ..Params::default()
+        };
+        api.get_folder(&params).await.unwrap();
+        assert_eq!(api.cache_folder.iter().count(), 1);
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let id = api.watch(base_dir.to_string_lossy().as_ref(), false, move |events| {
+            let _ = tx.send(events);
+        }).unwrap();
+        std::fs::write(base_dir.join("new.txt"), b"x").unwrap();
+
+        let events = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(events.iter().any(|e| e.kind == WatchKind::Created && e.path.ends_with("new.txt")));
+        api.cache_folder.run_pending_tasks().await;
+        assert_eq!(api.cache_folder.iter().count(), 0);
+
+        assert!(api.unwatch(id).unwrap());
+        assert!(!api.unwatch(id).unwrap());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tail() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-tail");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let path = base_dir.join("a.log");
+        std::fs::write(&path, "old\n").unwrap();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let id = api.tail(path.to_string_lossy().as_ref(), None, move |events| {
+            let _ = tx.send(events);
+        }).await.unwrap();
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"new\n").unwrap();
+
+        let events = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(events, vec![TailEvent { kind: TailKind::Lines, lines: Some(vec![String::from("new")]) }]);
+        assert!(api.unwatch(id).unwrap());
+
+        // EUC-KR, detected from the file or given like the viewer's `enc`
+        let old_text = "기존 로그 줄입니다.\n".repeat(20);
+        let (euc_old, _, _) = encoding_rs::EUC_KR.encode(&old_text);
+        let (euc_new, _, _) = encoding_rs::EUC_KR.encode("새 줄\n");
+        for (content, encoding) in [(euc_old.to_vec(), None), (b"old\n".to_vec(), Some("euc-kr"))] {
+            std::fs::write(&path, &content).unwrap();
+            let (tx, rx) = std::sync::mpsc::channel();
+            let id = api.tail(path.to_string_lossy().as_ref(), encoding, move |events| {
+                let _ = tx.send(events);
+            }).await.unwrap();
+            let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+            file.write_all(&euc_new).unwrap();
+            let events = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert_eq!(events, vec![TailEvent { kind: TailKind::Lines, lines: Some(vec![String::from("새 줄")]) }]);
+            assert!(api.unwatch(id).unwrap());
+        }
+        assert!(api.tail(path.to_string_lossy().as_ref(), Some("no-such"), |_| {}).await.is_err());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_compute_dir_size() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-dir-size");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("a").join("b")).unwrap();
+        std::fs::write(base_dir.join("a").join("x"), vec![0u8; 100]).unwrap();
+        std::fs::write(base_dir.join("a").join("b").join("y"), vec![0u8; 20]).unwrap();
+        std::fs::write(base_dir.join("z"), vec![0u8; 3]).unwrap();
+
+        let dir_size = api.compute_dir_size(base_dir.to_string_lossy().as_ref(), |_| {}).await.unwrap();
+        assert_eq!(dir_size.sz, 123);
+        assert_eq!(dir_size.files, 3);
+        assert_eq!(dir_size.dirs, 2);
+        assert!(dir_size.canceled.is_none());
+        assert!(!api.cancel_dir_size(base_dir.to_string_lossy().as_ref()).unwrap());
+
+        let params = Params {
+            path_str: base_dir.to_string_lossy().to_string(),
+            meta_types: vec![MetaType::Sz, MetaType::DirSz],
+            ..Params::default()
+        };
+        let items = api.get_folder(&params).await.unwrap().item.items.unwrap();
+        assert_eq!(items[0].nm, "a");
+        assert_eq!(items[0].sz, Some(120));
+        assert_eq!(items[1].sz, Some(3));
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_analyze_disk_usage() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-disk-usage");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("big").join("deep")).unwrap();
+        std::fs::write(base_dir.join("big").join("deep").join("x"), vec![0u8; 600]).unwrap();
+        std::fs::write(base_dir.join("big").join("y"), vec![0u8; 200]).unwrap();
+        std::fs::write(base_dir.join("a"), vec![0u8; 100]).unwrap();
+        std::fs::write(base_dir.join("b"), vec![0u8; 60]).unwrap();
+        std::fs::write(base_dir.join("c"), vec![0u8; 40]).unwrap();
+
+        let disk_usage = api.analyze_disk_usage(base_dir.to_string_lossy().as_ref(), 2, 2, |_| {}).await.unwrap();
+        std::fs::remove_dir_all(&base_dir).unwrap();
+
+        let root = disk_usage.root.unwrap();
+        assert_eq!(root.sz, 1000);
+        assert_eq!(root.pct, 100.0);
+        assert_eq!(root.others_sz, Some(100));
+        assert_eq!(root.others_cnt, Some(2));
+        let children = root.children.unwrap();
+        assert_eq!(children.iter().map(|c| c.nm.as_str()).collect::<Vec<_>>(), ["big", "a"]);
+        assert_eq!(children[0].pct, 80.0);
+        let deep = &children[0].children.as_ref().unwrap()[0];
+        assert_eq!(deep.nm, "deep");
+        assert_eq!(deep.pct_parent, 75.0);
+        assert!(deep.children.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_read_text_range() {
+        let base = std::env::temp_dir().join("napi-folder-test-text-range");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let api = Api { cache_dir: base.join("cache"), ..Api::default() };
+
+        let path = base.join("a.log");
+        let mut text: String = (0..3000).map(|i| format!("{} 한글 라인\n", i)).collect();
+        text.push_str(&"가".repeat(500));
+        std::fs::write(&path, &text).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let page = api.read_text_range(&path_str, None, Some(2500), Some(2), None).await.unwrap();
+        assert_eq!(page.text, "2500 한글 라인\n2501 한글 라인\n");
+        assert_eq!(page.tot_lines, 3001);
+        assert_eq!(page.next_line, 2502);
+
+        // odd page sizes never split a character
+        let mut all = String::new();
+        let mut offset = 0;
+        loop {
+            let page = api.read_text_range(&path_str, Some(offset), None, None, Some(1001)).await.unwrap();
+            assert!(!page.had_errors);
+            all.push_str(&page.text);
+            offset = page.next_offset;
+            if page.eof {
+                break;
+            }
+        }
+        assert_eq!(all, text);
+
+        // an offset inside a character moves to the next one
+        let page = api.read_text_range(&path_str, Some(3), None, Some(1), None).await.unwrap();
+        assert_eq!((page.offset, page.line_start, page.text.as_str()), (5, 0, "글 라인\n"));
+
+        let (euc, _, _) = encoding_rs::EUC_KR.encode(&text);
+        let euc_path = base.join("euc.txt");
+        std::fs::write(&euc_path, &euc).unwrap();
+        let euc_path_str = euc_path.to_string_lossy().to_string();
+        let mut all = String::new();
+        let mut offset = 0;
+        loop {
+            let page = api.read_text_range(&euc_path_str, Some(offset), None, None, Some(777)).await.unwrap();
+            assert_eq!(page.enc, "EUC-KR");
+            all.push_str(&page.text);
+            offset = page.next_offset;
+            if page.eof {
+                break;
+            }
+        }
+        assert_eq!(all, text);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
     #[tokio::test]
     async fn test_state() {
         let api = Api::default();
@@ -422,6 +1615,389 @@ mod tests {
     }
 
 
+    #[tokio::test]
+    async fn test_read_txt_encoding() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-read-txt");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+
+        let text = "한글 텍스트 파일입니다.\n".repeat(20);
+        let (euc, _, _) = encoding_rs::EUC_KR.encode(&text);
+        let mut broken = euc.into_owned();
+        broken.insert(11, 0xff);
+        let path = base_dir.join("cp949.txt");
+        std::fs::write(&path, &broken).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let content = api.read_txt(&path_str, None, false).await.unwrap();
+        assert_eq!((content.enc.as_deref(), content.text.as_deref(), content.err_cnt), (Some("EUC-KR"), None, Some(1)));
+        let content = api.read_txt(&path_str, None, true).await.unwrap();
+        assert!(content.text.unwrap().starts_with("한글 텍스트\u{fffd} "));
+        assert_eq!(content.candidates.unwrap()[0].enc, "EUC-KR");
+        let content = api.read_txt(&path_str, Some("utf-8"), true).await.unwrap();
+        assert_eq!(content.enc.as_deref(), Some("UTF-8"));
+        assert!(content.candidates.is_none());
+        assert!(content.err_cnt.unwrap() > 1);
+        assert!(api.read_txt(&path_str, Some("no-such"), false).await.is_err());
+
+        let path = base_dir.join("bom.txt");
+        std::fs::write(&path, b"\xef\xbb\xbfabc").unwrap();
+        let content = api.read_txt(path.to_string_lossy().as_ref(), None, false).await.unwrap();
+        assert_eq!((content.bom, content.text.as_deref()), (Some(true), Some("abc")));
+        // the encoding of the byte order mark leads the candidates, even when it is not ASCII-compatible
+        let path = base_dir.join("utf16.txt");
+        std::fs::write(&path, b"\xff\xfea\x00b\x00").unwrap();
+        let content = api.read_txt(path.to_string_lossy().as_ref(), None, false).await.unwrap();
+        assert_eq!((content.enc.as_deref(), content.text.as_deref()), (Some("UTF-16LE"), Some("ab")));
+        let candidates = content.candidates.unwrap();
+        assert_eq!((candidates[0].enc.as_str(), candidates[0].err_cnt), ("UTF-16LE", 0));
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_write_text() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-write-text");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let path = base_dir.join("a.txt");
+        let path_str = path.to_string_lossy().to_string();
+        let (euc, _, _) = encoding_rs::EUC_KR.encode("첫 줄\r\n둘째 줄\r\n");
+        std::fs::write(&path, &euc).unwrap();
+
+        let content = api.read_txt(&path_str, Some("euc-kr"), false).await.unwrap();
+        assert_eq!(content.line_ending, Some(LineEnding::Crlf));
+        let text = content.text.unwrap().replace("둘째", "셋째").replace("\r\n", "\n");
+        let tm = api.write_text(&path_str, &text, "EUC-KR", Some(LineEnding::Crlf), content.tm.as_deref()).await.unwrap();
+        let (expected, _, _) = encoding_rs::EUC_KR.encode("첫 줄\r\n셋째 줄\r\n");
+        assert_eq!(std::fs::read(&path).unwrap(), expected.to_vec());
+
+        // a change within the same second is still seen
+        let changed = (tm.parse::<u128>().unwrap() + 1).to_string();
+        assert!(api.write_text(&path_str, "x", "EUC-KR", None, Some(&changed)).await.is_err());
+        assert!(api.write_text(&path_str, "😀", "EUC-KR", None, Some(&tm)).await.is_err());
+        assert_eq!(std::fs::read_dir(&base_dir).unwrap().count(), 1);
+
+        // saved through a symlink, the link stays
+        #[cfg(unix)]
+        {
+            let link = base_dir.join("link.txt");
+            std::os::unix::fs::symlink(&path, &link).unwrap();
+            api.write_text(link.to_string_lossy().as_ref(), "linked", "UTF-8", None, None).await.unwrap();
+            assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+            assert_eq!(std::fs::read(&path).unwrap(), b"linked");
+            std::fs::remove_file(&link).unwrap();
+        }
+
+        let bom_path = base_dir.join("bom.txt");
+        std::fs::write(&bom_path, b"\xef\xbb\xbfold").unwrap();
+        api.write_text(bom_path.to_string_lossy().as_ref(), "new", "UTF-8", None, None).await.unwrap();
+        assert_eq!(std::fs::read(&bom_path).unwrap(), b"\xef\xbb\xbfnew");
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_search_in_file() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-search-in-file");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let path = base_dir.join("a.log");
+        let text: String = (0..5000).map(|i| format!("{} 로그 {}\n", i, if i % 1000 == 7 { "ERROR" } else { "ok" })).collect();
+        let (euc, _, _) = encoding_rs::EUC_KR.encode(&text);
+        std::fs::write(&path, &euc).unwrap();
+
+        let res = api.search_in_file(path.to_string_lossy().as_ref(), "로그 error", false, false, 3).await.unwrap();
+        assert_eq!(res.enc, "EUC-KR");
+        assert!(res.truncated);
+        let lines: Vec<u64> = res.hits.iter().map(|hit| hit.line_no).collect();
+        assert_eq!(lines, vec![7, 1007, 2007]);
+        assert_eq!(res.hits[0].snippet, "7 로그 ERROR");
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_grep() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-grep");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("sub")).unwrap();
+        std::fs::write(base_dir.join("a.txt"), "needle\nhay\n").unwrap();
+        std::fs::write(base_dir.join("sub/b.txt"), "hay needle needle\n").unwrap();
+        let root = base_dir.to_string_lossy().to_string();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let opt = GrepOpt { max_hits: Some(2), ..GrepOpt::default() };
+        let summary = api.grep(&root, "NEEDLE", &opt, move |file| {
+            let _ = tx.send(file);
+        }).await.unwrap();
+        assert!(summary.truncated);
+        assert_eq!(summary.hits, 2);
+        let sent: usize = rx.try_iter().map(|file| file.hits.len()).sum();
+        assert_eq!(sent, 2);
+        assert!(!api.cancel_grep(&root).unwrap());
+        assert!(api.grep(&root, "(", &GrepOpt { regex: Some(true), ..GrepOpt::default() }, |_| {}).await.is_err());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_find() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-find");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("docs/notes")).unwrap();
+        std::fs::write(base_dir.join("docs/notes/todo.md"), "").unwrap();
+        std::fs::write(base_dir.join("docs/readme.md"), "").unwrap();
+        std::fs::write(base_dir.join("main.rs"), "").unwrap();
+        let root = base_dir.to_string_lossy().to_string();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let result = api.find(&root, "*.md", FindMode::Glob, 1, move |hits| {
+            let _ = tx.send(hits);
+        }).await.unwrap();
+        assert_eq!(result.total, 2);
+        assert_eq!(result.hits.len(), 1);
+        assert_eq!(result.hits[0].rel, "docs/readme.md");
+        assert_eq!(result.hits[0].item.nm, "readme.md");
+        assert_eq!(result.canceled, None);
+        let sent: usize = rx.try_iter().map(|hits| hits.len()).sum();
+        assert_eq!(sent, 2);
+
+        let result = api.find(&root, "dnt", FindMode::Fuzzy, 10, |_| {}).await.unwrap();
+        assert_eq!(result.hits[0].rel, "docs/notes/todo.md");
+        assert!(!api.cancel_find(&root).unwrap());
+        assert!(api.find(&root, "(", FindMode::Regex, 10, |_| {}).await.is_err());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_name_index() {
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-name-index");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        let root_dir = base_dir.join("root");
+        std::fs::create_dir_all(root_dir.join("music")).unwrap();
+        std::fs::write(root_dir.join("music/song.mp3"), "mp3").unwrap();
+        let api = Api { cache_dir: base_dir.join("cache"), ..Api::default() };
+        let root = root_dir.to_string_lossy().to_string();
+
+        assert!(api.search_name_index(&root, "song", FindMode::Substring, 10).await.is_err());
+        assert!(api.get_name_index_info(&root).await.unwrap().is_none());
+        let info = api.update_name_index(&root, |_| {}).await.unwrap();
+        assert_eq!((info.dirs, info.files, info.read_dirs), (2, 1, Some(2)));
+        assert!(!api.cancel_name_index(&root).unwrap());
+
+        // a new instance loads the saved index
+        let api = Api { cache_dir: base_dir.join("cache"), ..Api::default() };
+        let result = api.search_name_index(&root, "SONG", FindMode::Substring, 10).await.unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.hits[0].rel, "music/song.mp3");
+        let info = api.update_name_index(&root, |_| {}).await.unwrap();
+        assert_eq!(info.read_dirs, Some(0));
+        assert!(api.get_name_index_info(&root).await.unwrap().is_some_and(|info| info.tm > 0));
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_text_index() {
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-text-index");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        let root_dir = base_dir.join("root");
+        std::fs::create_dir_all(&root_dir).unwrap();
+        std::fs::write(root_dir.join("todo.md"), "intro\n- fix the Viewer scroll\n- viewer zoom\n").unwrap();
+        std::fs::write(root_dir.join("log.txt"), "nothing here\n").unwrap();
+        let api = Api { cache_dir: base_dir.join("cache"), ..Api::default() };
+        let root = root_dir.to_string_lossy().to_string();
+
+        assert!(api.search_text_index(&root, "viewer", 10, 5).await.is_err());
+        let opt = TextIndexOpt { include: Some(vec![String::from("*.md")]), ..TextIndexOpt::default() };
+        let info = api.update_text_index(&root, Some(opt), |_| {}).await.unwrap();
+        assert_eq!((info.docs, info.read_docs), (1, Some(1)));
+        assert!(!api.cancel_text_index(&root).unwrap());
+
+        // a new instance loads the saved index and its options
+        let api = Api { cache_dir: base_dir.join("cache"), ..Api::default() };
+        let result = api.search_text_index(&root, "view", 10, 1).await.unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.hits[0].rel, "todo.md");
+        assert_eq!(result.hits[0].hits.len(), 1);
+        assert_eq!(result.hits[0].hits[0].line_no, 1);
+        assert_eq!(result.hits[0].hits[0].snippet, "- fix the Viewer scroll");
+        let info = api.update_text_index(&root, None, |_| {}).await.unwrap();
+        assert_eq!((info.docs, info.read_docs), (1, Some(0)));
+
+        assert!(api.remove_text_index(&root).await.unwrap());
+        assert!(api.get_text_index_info(&root).await.unwrap().is_none());
+        assert!(!api.remove_text_index(&root).await.unwrap());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_hex() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-hex");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let path = base_dir.join("a.bin");
+        let mut data = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+        data.extend(vec![0u8; 100]);
+        data.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        std::fs::write(&path, &data).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let page = api.read_hex(&path_str, None, None, Some(8)).await.unwrap();
+        assert_eq!(page.sz, 112);
+        assert_eq!(page.rows.len(), 14);
+        assert_eq!(page.rows[0].hex, "7f 45 4c 46 02 01 01 00");
+        assert_eq!(page.rows[0].ascii, ".ELF....");
+        assert!(page.eof);
+
+        let result = api.find_bytes(&path_str, "DE AD be ef", true, None, 10).await.unwrap();
+        assert_eq!((result.len, result.offsets, result.truncated), (4, vec![108], false));
+        let result = api.find_bytes(&path_str, "ELF", false, Some(2), 10).await.unwrap();
+        assert!(result.offsets.is_empty());
+        assert!(api.find_bytes(&path_str, "xyz", true, None, 10).await.is_err());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_read_bytes() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-read-bytes");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let path = base_dir.join("a.bin");
+        let data: Vec<u8> = (0..4096u32).map(|i| (i % 256) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        assert_eq!(api.read_bytes(&path_str, None, None).await.unwrap(), data);
+        assert_eq!(api.read_bytes(&path_str, Some(4000), Some(1000)).await.unwrap(), data[4000..]);
+        assert!(api.read_bytes(&path_str, Some(5000), None).await.unwrap().is_empty());
+
+        let config: ApiConfig = serde_json::from_str(r#"{"max_read_bytes": 1024, "mmap_min_bytes": 512}"#).unwrap();
+        api.set_config(config).unwrap();
+        assert!(api.read_bytes(&path_str, None, None).await.is_err());
+        assert_eq!(api.read_bytes(&path_str, Some(100), Some(1024)).await.unwrap(), data[100..1124]);
+        let config: ApiConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(api.set_config(config).unwrap(), ApiConfig::default());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    /// a request with `Connection: close`, the response head and body
+    async fn http_get(port: u16, target: &str, range: Option<&str>) -> (String, Vec<u8>) {
+        use tokio::io::AsyncReadExt;
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let range = range.map(|range| format!("Range: {}\r\n", range)).unwrap_or_default();
+        let req = format!("GET {} HTTP/1.1\r\nHost: localhost\r\n{}Connection: close\r\n\r\n", target, range);
+        stream.write_all(req.as_bytes()).await.unwrap();
+        let mut resp = vec![];
+        stream.read_to_end(&mut resp).await.unwrap();
+        let split = resp.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        (String::from_utf8_lossy(&resp[..split]).into_owned(), resp[split + 4..].to_vec())
+    }
+
+    #[tokio::test]
+    async fn test_media_server() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-media");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("music")).unwrap();
+        let data: Vec<u8> = (0..1000u32).map(|i| (i % 256) as u8).collect();
+        std::fs::write(base_dir.join("music/a song.mp3"), &data).unwrap();
+        std::fs::write(base_dir.join("secret.txt"), "secret").unwrap();
+        let root = base_dir.join("music").to_string_lossy().to_string();
+
+        let info = api.start_media_server(&[root]).await.unwrap();
+        let encode = |path: &Path| path.to_string_lossy().bytes().map(|b| format!("%{:02X}", b)).collect::<String>();
+        let song = encode(&base_dir.join("music/a song.mp3"));
+        let target = format!("/{}/{}", info.token, song);
+
+        let (head, body) = http_get(info.port, &target, None).await;
+        assert!(head.starts_with("HTTP/1.1 200 OK"), "{}", head);
+        assert!(head.contains("Content-Type: audio/mpeg"));
+        assert_eq!(body, data);
+
+        let (head, body) = http_get(info.port, &target, Some("bytes=100-199")).await;
+        assert!(head.starts_with("HTTP/1.1 206"), "{}", head);
+        assert!(head.contains("Content-Range: bytes 100-199/1000"));
+        assert_eq!(body, data[100..200]);
+
+        let (head, _) = http_get(info.port, &target, Some("bytes=5000-")).await;
+        assert!(head.starts_with("HTTP/1.1 416"), "{}", head);
+        let (head, _) = http_get(info.port, &format!("/{}/{}", info.token, encode(&base_dir.join("secret.txt"))), None).await;
+        assert!(head.starts_with("HTTP/1.1 403"), "{}", head);
+        let (head, _) = http_get(info.port, &format!("/{}/{}", info.token, encode(&base_dir.join("music/../secret.txt"))), None).await;
+        assert!(head.starts_with("HTTP/1.1 403"), "{}", head);
+        let (head, _) = http_get(info.port, &format!("/wrong/{}", song), None).await;
+        assert!(head.starts_with("HTTP/1.1 403"), "{}", head);
+
+        assert!(api.stop_media_server(&info.token).unwrap());
+        assert!(!api.stop_media_server(&info.token).unwrap());
+        assert!(api.start_media_server(&[base_dir.join("missing").to_string_lossy().to_string()]).await.is_err());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_thumbnail() {
+        use image::GenericImageView;
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-thumb");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        let api = Api { thumb_dir: base_dir.join("thumbnails"), ..Api::default() };
+        let path = base_dir.join("photo.png");
+        image::RgbImage::from_pixel(300, 600, image::Rgb([10, 200, 30])).save(&path).unwrap();
+        let path_str = path.to_string_lossy().to_string();
+
+        let png = api.get_thumbnail(&path_str, 256, ThumbFormat::Png).await.unwrap();
+        assert_eq!(image::load_from_memory(&png).unwrap().dimensions(), (128, 256));
+        let thumb = thumb_path(&base_dir.join("thumbnails"), &file_uri(&path), "large");
+        assert_eq!(std::fs::read(&thumb).unwrap(), png);
+
+        // scaled down from the cached `large` thumbnail
+        let png = api.get_thumbnail(&path_str, 200, ThumbFormat::Png).await.unwrap();
+        assert_eq!(image::load_from_memory(&png).unwrap().dimensions(), (100, 200));
+        // a broken cache file is replaced
+        std::fs::write(&thumb, b"").unwrap();
+        api.get_thumbnail(&path_str, 256, ThumbFormat::Png).await.unwrap();
+        assert!(load_cached(&thumb, &file_uri(&path), std::fs::metadata(&path).unwrap().modified().unwrap().to_sec()).is_some());
+        let jpeg = api.get_thumbnail(&path_str, 100, ThumbFormat::Jpeg).await.unwrap();
+        assert_eq!(image::load_from_memory(&jpeg).unwrap().dimensions(), (50, 100));
+        assert!(api.get_thumbnail(&base_dir.join("none.png").to_string_lossy(), 128, ThumbFormat::Png).await.is_err());
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_image_dims() {
+        let api = Api::default();
+        let base_dir = std::env::temp_dir().join("napi-folder-test-api-image-dims");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        image::RgbImage::new(40, 30).save(base_dir.join("a.png")).unwrap();
+        image::RgbImage::new(20, 10).save(base_dir.join("b.png")).unwrap();
+        std::fs::write(base_dir.join("c.txt"), "text").unwrap();
+
+        let info = api.read_image_info(&base_dir.join("a.png").to_string_lossy()).await.unwrap();
+        assert_eq!((info.format.as_str(), info.width, info.height), ("png", 40, 30));
+
+        let params = Params {
+            path_str: base_dir.to_string_lossy().to_string(),
+            meta_types: vec![MetaType::Dim],
+            ordering: vec![OrdItem { nm: OrderBy::Dim, asc: OrderAsc::Asc, cmp: None, locale: None }],
+            cache_nm: Some(String::from("dims")),
+            ..Params::default()
+        };
+        api.get_folder(&params).await.unwrap();
+        // from the cache
+        let folder = api.get_folder(&params).await.unwrap();
+        let items = folder.item.items.unwrap();
+        let dims: Vec<(&str, Option<u32>, Option<u32>)> = items.iter()
+            .map(|item| (item.nm.as_str(), item.width, item.height))
+            .collect();
+        assert_eq!(dims, vec![("b.png", Some(20), Some(10)), ("a.png", Some(40), Some(30)), ("c.txt", None, None)]);
+        std::fs::remove_dir_all(&base_dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_read_txt() {
         let api = Api::default();
@@ -430,7 +2006,7 @@ mod tests {
         // let s = r"c:\docs\t1.json";
         let s = r"C:\Users\kkt\Downloads\vite.main.config.ts";
         // let s = r"C:\sources\sample\header-logo.png";
-        match api.read_txt(s).await {
+        match api.read_txt(s, None, false).await {
             Ok(text_content) => {
                 println!("{:?}", text_content);
             },
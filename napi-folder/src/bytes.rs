@@ -0,0 +1,80 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use crate::models::ApiError;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+///
+/// `len` bytes of `file` from `offset`, or fewer at the end of the file
+///
+/// With `mmap` the range is copied out of a read-only mapping of the file (unix) instead of being
+/// read with syscalls. Other platforms always read.
+pub fn read_range(file: &mut File, offset: u64, len: usize, mmap: bool) -> Result<Vec<u8>> {
+    #[cfg(unix)]
+    if mmap && len > 0 {
+        let sz = file.metadata()?.len();
+        let len = len.min(sz.saturating_sub(offset) as usize);
+        if len == 0 {
+            return Ok(vec![]);
+        }
+        let bytes = read_mapped(file, offset, len)?;
+        // a file cut while it was copied may have left pages of zeros, read it the plain way then
+        if file.metadata()?.len() >= offset + len as u64 {
+            return Ok(bytes);
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = mmap;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::with_capacity(len);
+    file.take(len as u64).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+///
+/// copy of `len` bytes from `offset`, which must be within the file
+///
+/// Only the mapped range is touched. A file cut below it during the copy still raises SIGBUS,
+/// which is why mapping is opt-in with `ApiConfig.mmap_min_bytes`.
+#[cfg(unix)]
+fn read_mapped(file: &File, offset: u64, len: usize) -> Result<Vec<u8>> {
+    use std::os::fd::AsRawFd;
+    let page = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+    let start = offset / page * page;
+    let skip = (offset - start) as usize;
+    let map_len = skip + len;
+    let ptr = unsafe {
+        libc::mmap(std::ptr::null_mut(), map_len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), start as libc::off_t)
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(std::io::Error::last_os_error().into());
+    }
+    let bytes = unsafe { std::slice::from_raw_parts((ptr as *const u8).add(skip), len) }.to_vec();
+    unsafe { libc::munmap(ptr, map_len) };
+    Ok(bytes)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_range() {
+        let base = std::env::temp_dir().join("napi-folder-test-bytes");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let path = base.join("data.bin");
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        let mut file = File::open(&path).unwrap();
+        for mmap in [false, true] {
+            assert_eq!(read_range(&mut file, 5000, 100, mmap).unwrap(), data[5000..5100]);
+            assert_eq!(read_range(&mut file, 19_990, 100, mmap).unwrap(), data[19_990..]);
+            assert!(read_range(&mut file, 30_000, 100, mmap).unwrap().is_empty());
+            assert!(read_range(&mut file, 0, 0, mmap).unwrap().is_empty());
+        }
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -1,7 +1,19 @@
 use std::cmp::Ordering;
-use crate::models::{Item, MetaType, ApiError, OrderAsc, OrdItem, OrderBy};
+use crate::models::{Item, MetaType, ApiError, OrderAsc, OrdItem, OrderBy, OrderCmp, Params};
 use crate::system_time_ext::SystemTimeExt;
-use std::path::{PathBuf};
+use crate::filter::Filter;
+use crate::metadata_ext::MetaExt;
+use crate::image_info::read_dim;
+use std::cmp;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::fs::DirEntry;
+use rayon::prelude::*;
+use icu_collator::{Collator, CollatorOptions, Numeric};
+use icu_locid::Locale;
+#[cfg(windows)]
 use windows::{
     core::{
         PCWSTR
@@ -16,16 +28,31 @@ use windows::{
         GetFullPathNameW,
     },
 };
+#[cfg(windows)]
 use std::ffi::OsStr;
+#[cfg(windows)]
 use std::os::windows::ffi::{OsStrExt};
 use mime_guess::from_path;
-use windows::Win32::Storage::FileSystem::FILE_ATTRIBUTE_DIRECTORY;
+#[cfg(windows)]
+use windows::Win32::Storage::FileSystem::{
+    FILE_ATTRIBUTE_DIRECTORY, FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_READONLY, FILE_ATTRIBUTE_REPARSE_POINT,
+};
+#[cfg(windows)]
+use crate::metadata_ext::{readonly_mode, rwx_str};
+#[cfg(windows)]
 use windows::core::Error as WinError;
 
 
 type Result<T> = std::result::Result<T, ApiError>;
 
+/// time budget of `update_children` per listing
+const CHILDREN_BUDGET: Duration = Duration::from_millis(300);
+/// time budget of `update_dims` per listing
+const DIMS_BUDGET: Duration = Duration::from_millis(1000);
+
+#[cfg(windows)]
 pub struct FindHandle(HANDLE);
+#[cfg(windows)]
 impl Drop for FindHandle {
     fn drop(&mut self) {
         match unsafe { FindClose(self.0) } {
@@ -35,14 +62,64 @@ impl Drop for FindHandle {
     }
 }
 
-#[allow(dead_code)]
-pub fn get_items(p: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>> {
-    let result = std::fs::read_dir(p)?.flatten()
-        .filter_map(|entry| { get_item_data(entry.path(), &meta_types) }).collect();
+/// Lists the entries of `p` with the native backend of the current platform
+/// and fills every requested `MetaType`.
+///
+/// Win32 uses `FindFirstFileExW`, everything else `std::fs::read_dir`
+/// (`getdents` on Linux).
+pub fn read_items(p: &str, meta_types: &[MetaType]) -> Result<Vec<Item>> {
+    #[cfg(windows)]
+    let mut items = get_items_win32(p, meta_types)?;
+    #[cfg(not(windows))]
+    let mut items = get_items(p, meta_types)?;
+    update_items(&mut items, meta_types);
+    update_children(p, &mut items, meta_types);
+    Ok(items)
+}
+
+/// `read_items` narrowed by `filter`.
+///
+/// Meta types the filter needs are read as well and cleared again if not requested.
+/// With `keep_dirs` the filter only applies to files, so a tree can still descend.
+pub fn list_items(p: &str, meta_types: &[MetaType], filter: Option<&Filter>, keep_dirs: bool) -> Result<Vec<Item>> {
+    let Some(filter) = filter else {
+        return read_items(p, meta_types);
+    };
+    let mut read_meta_types = meta_types.to_vec();
+    let extra: Vec<MetaType> = filter.meta_types().into_iter()
+        .filter(|meta_type| !meta_types.contains(meta_type))
+        .collect();
+    read_meta_types.extend(extra.iter().cloned());
+
+    let mut items = filter.apply(read_items(p, &read_meta_types)?, keep_dirs);
+    for item in items.iter_mut() {
+        if extra.contains(&MetaType::Sz) {
+            item.sz = None;
+        }
+        if extra.contains(&MetaType::Tm) {
+            item.tm = None;
+        }
+    }
+    Ok(items)
+}
+
+/// Portable listing backend.
+///
+/// The directory flag comes from the `d_type` of the entry, so `stat` is only
+/// called (in parallel) when a `STAT_META_TYPES` is requested or the entry is a symlink.
+#[cfg_attr(windows, allow(dead_code))]
+pub fn get_items(p: &str, meta_types: &[MetaType]) -> Result<Vec<Item>> {
+    let entries: Vec<DirEntry> = std::fs::read_dir(p)?.flatten().collect();
+    let result = if needs_stat(meta_types) && entries.len() > 1 {
+        entries.par_iter().filter_map(|entry| get_entry_data(entry, meta_types)).collect()
+    } else {
+        entries.iter().filter_map(|entry| get_entry_data(entry, meta_types)).collect()
+    };
     Ok(result)
 }
 
-pub fn get_items_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>> {
+#[cfg(windows)]
+pub fn get_items_win32(p: &str, meta_types: &[MetaType]) -> Result<Vec<Item>> {
     let mut result = Vec::new();
     // let pattern = format!("{}/*", p);
     let pattern: Vec<u16> = OsStr::new(&format!("{}/*", p))
@@ -64,7 +141,7 @@ pub fn get_items_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>>
     };
     let _handle_guard = FindHandle(handle);
     loop {
-        if let Some(item) = get_item_data_win32(&mut find_data, &meta_types) {
+        if let Some(item) = get_item_data_win32(p, &mut find_data, &meta_types) {
             result.push(item);
         }
         match unsafe { FindNextFileW(handle, &mut find_data) } {
@@ -75,8 +152,9 @@ pub fn get_items_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Vec<Item>>
     Ok(result)
 }
 
+#[cfg(windows)]
 #[allow(dead_code)]
-fn get_item_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Option<Item>> {
+fn get_item_win32(p: &str, meta_types: &[MetaType]) -> Result<Option<Item>> {
     let pattern: Vec<u16> = OsStr::new(&format!("{}/*", p))
         .encode_wide()
         .chain(Some(0))
@@ -95,52 +173,82 @@ fn get_item_win32(p: &str, meta_types: &Vec<MetaType>) -> Result<Option<Item>> {
         )?
     };
     let _handle_guard = FindHandle(handle);
-    Ok(get_item_data_win32(&mut find_data, meta_types))
+    Ok(get_item_data_win32(p, &mut find_data, meta_types))
 }
 
 
 
-// fn get_item_data(entry: DirEntry, meta_types: Option<Vec<MetaType>>) -> Option<Item> {
-fn get_item_data(p: PathBuf, meta_types: &Vec<MetaType>) -> Option<Item> {
-    // let nm = entry.file_name().to_string_lossy().to_string();
-    // let p = PathBuf::from(nm.clone());
-    let nm = p.file_name()?.to_string_lossy().to_string();
-    let dir = p.is_dir();
-    let mut ext = None;
-    let mut sz = None;
-    let mut tm = None;
+/// meta types that need a `stat` of the entry
+const STAT_META_TYPES: [MetaType; 10] = [
+    MetaType::Sz, MetaType::Tm, MetaType::Ctime, MetaType::Atime, MetaType::Mode,
+    MetaType::Owner, MetaType::Group, MetaType::Readonly, MetaType::Inode, MetaType::Nlink,
+];
 
-        if meta_types.contains(&MetaType::Ext) {
-            ext = p.extension()
-                .map(|ext| ext.to_string_lossy().to_string().to_lowercase());
-        }
+fn needs_stat(meta_types: &[MetaType]) -> bool {
+    meta_types.iter().any(|meta_type| STAT_META_TYPES.contains(meta_type))
+}
 
-    match p.metadata() {
-        Ok(metadata) => {
-            if meta_types.contains(&MetaType::Sz) {
-                sz = Some(metadata.len());
-            }
-            if meta_types.contains(&MetaType::Tm) {
-                tm = metadata.modified().map(|t|t.to_sec()).ok();
-            }
-        }
-        Err(err) => {
-            println!("{:?}", err);
+fn get_entry_data(entry: &DirEntry, meta_types: &[MetaType]) -> Option<Item> {
+    let file_type = entry.file_type().ok()?;
+    let mut item = if file_type.is_symlink() || needs_stat(meta_types) {
+        get_item_data(entry.path(), meta_types)?
+    } else {
+        Item {
+            nm: entry.file_name().to_string_lossy().to_string(),
+            dir: file_type.is_dir(),
+            ..Item::default()
         }
     };
+    if meta_types.contains(&MetaType::Hidden) {
+        item.hidden = Some(item.nm.starts_with('.'));
+    }
+    if meta_types.contains(&MetaType::Symlink) && file_type.is_symlink() {
+        let p = entry.path();
+        item.symlink = std::fs::read_link(&p).ok().map(|target| target.to_string_lossy().into_owned());
+        item.broken = Some(!p.exists());
+    }
+    Some(item)
+}
 
-    Some(Item {
+fn get_item_data(p: PathBuf, meta_types: &[MetaType]) -> Option<Item> {
+    let nm = p.file_name()?.to_string_lossy().to_string();
+    let mut item = Item {
         nm,
-        dir,
-        ext,
-        tm,
-        sz,
         ..Item::default()
-    })
+    };
+
+    // follow symlinks like FindFirstFileExW does, fall back to the link itself if broken;
+    // an entry removed since it was listed is skipped
+    let metadata = p.metadata().or_else(|_| p.symlink_metadata()).ok()?;
+    item.dir = metadata.is_dir();
+    for meta_type in meta_types {
+        match meta_type {
+            MetaType::Sz => item.sz = Some(metadata.len()),
+            MetaType::Tm => item.tm = metadata.modified().map(|t|t.to_sec()).ok(),
+            MetaType::Ctime => item.ctime = metadata.created().map(|t|t.to_sec()).ok(),
+            MetaType::Atime => item.atime = metadata.accessed().map(|t|t.to_sec()).ok(),
+            MetaType::Mode => {
+                // of the link itself like `ls -l`, so a symlink shows as `l`
+                let link_metadata = p.symlink_metadata().ok();
+                let mode_metadata = link_metadata.as_ref().unwrap_or(&metadata);
+                item.mode = Some(mode_metadata.to_mode_str());
+                item.mode_oct = Some(mode_metadata.to_mode_oct());
+            }
+            MetaType::Owner => item.owner = metadata.owner_nm(),
+            MetaType::Group => item.group = metadata.group_nm(),
+            MetaType::Readonly => item.readonly = Some(metadata.permissions().readonly()),
+            MetaType::Inode => item.inode = metadata.inode(),
+            MetaType::Nlink => item.nlink = metadata.nlink(),
+            _ => {}
+        }
+    }
+
+    Some(item)
 }
 
 
-fn get_item_data_win32(find_data: &mut WIN32_FIND_DATAW, meta_types: &Vec<MetaType>) -> Option<Item> {
+#[cfg(windows)]
+fn get_item_data_win32(p: &str, find_data: &mut WIN32_FIND_DATAW, meta_types: &[MetaType]) -> Option<Item> {
     let nm = String::from_utf16_lossy(
         &find_data.cFileName[..find_data.cFileName.iter().position(|&c| c == 0).unwrap_or(0)],
     );
@@ -162,16 +270,48 @@ fn get_item_data_win32(find_data: &mut WIN32_FIND_DATAW, meta_types: &Vec<MetaTy
         tm = Some(filetime_to_unix_time(find_data.ftLastWriteTime));
     }
 
-    Some(Item {
+    let attrs = find_data.dwFileAttributes;
+    let readonly = (attrs & FILE_ATTRIBUTE_READONLY.0) != 0;
+    let mut item = Item {
         nm,
         dir,
         ext,
         tm,
         sz,
         ..Item::default()
-    })
+    };
+    for meta_type in meta_types {
+        match meta_type {
+            MetaType::Ctime => item.ctime = Some(filetime_to_unix_time(find_data.ftCreationTime)),
+            MetaType::Atime => item.atime = Some(filetime_to_unix_time(find_data.ftLastAccessTime)),
+            MetaType::Mode => {
+                let mode = readonly_mode(readonly);
+                item.mode = Some(format!("{}{}", if dir { 'd' } else { '-' }, rwx_str(mode)));
+                item.mode_oct = Some(format!("{:04o}", mode));
+            }
+            MetaType::Hidden => item.hidden = Some((attrs & FILE_ATTRIBUTE_HIDDEN.0) != 0),
+            MetaType::Readonly => item.readonly = Some(readonly),
+            MetaType::Symlink => {
+                let tag = find_data.dwReserved0;
+                if (attrs & FILE_ATTRIBUTE_REPARSE_POINT.0) != 0
+                    && (tag == IO_REPARSE_TAG_SYMLINK || tag == IO_REPARSE_TAG_MOUNT_POINT) {
+                    let link = PathBuf::from(p).join(&item.nm);
+                    item.symlink = std::fs::read_link(&link).ok().map(|target| target.to_string_lossy().into_owned());
+                    item.broken = Some(!link.exists());
+                }
+            }
+            _ => {}
+        }
+    }
+    Some(item)
 }
 
+#[cfg(windows)]
+const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000000C;
+#[cfg(windows)]
+const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA0000003;
+
+#[cfg(windows)]
 fn filetime_to_unix_time(filetime: FILETIME) -> u64 {
     let high = filetime.dwHighDateTime as u64;
     let low = filetime.dwLowDateTime as u64;
@@ -181,6 +321,7 @@ fn filetime_to_unix_time(filetime: FILETIME) -> u64 {
     unix_time_100ns / 10_000_000
 }
 
+#[cfg_attr(not(windows), allow(dead_code))]
 pub fn get_extension(filename: &str) -> Option<&str> {
     filename.rsplit_once('.').and_then(|(_, ext)| {
         if ext.is_empty() {
@@ -191,6 +332,7 @@ pub fn get_extension(filename: &str) -> Option<&str> {
     })
 }
 
+#[cfg(windows)]
 #[allow(dead_code)]
 pub fn get_full_path(path: &str) -> Result<String> {
     unsafe {
@@ -214,12 +356,81 @@ pub fn get_full_path(path: &str) -> Result<String> {
     }
 }
 
-#[allow(dead_code)]
+#[cfg_attr(windows, allow(dead_code))]
 fn has_children (path: &str) -> std::io::Result<bool> {
     Ok(std::fs::read_dir(PathBuf::from(path))?.flatten().next().is_some())
 }
 
-#[allow(dead_code)]
+/// `None` if `deadline` passes while counting
+fn count_children(path: &str, deadline: Instant) -> Option<usize> {
+    let mut cnt = 0;
+    for (i, _) in std::fs::read_dir(PathBuf::from(path)).ok()?.flatten().enumerate() {
+        if i % 1024 == 1023 && Instant::now() > deadline {
+            return None;
+        }
+        cnt += 1;
+    }
+    Some(cnt)
+}
+
+///
+/// fill `child_cnt`/`has_children` of the directory items of `p` in parallel
+///
+/// Directories not reached within `CHILDREN_BUDGET` keep `None`.
+pub fn update_children(p: &str, items: &mut [Item], meta_types: &[MetaType]) {
+    let want_cnt = meta_types.contains(&MetaType::ChildCnt);
+    let want_has = meta_types.contains(&MetaType::HasChildren);
+    if !want_cnt && !want_has {
+        return;
+    }
+    let deadline = Instant::now() + CHILDREN_BUDGET;
+    items.par_iter_mut().filter(|item| item.dir).for_each(|item| {
+        if Instant::now() > deadline {
+            return;
+        }
+        let path = PathBuf::from(p).join(&item.nm);
+        let path = path.to_string_lossy();
+        if want_cnt {
+            item.child_cnt = count_children(&path, deadline);
+            if want_has {
+                item.has_children = item.child_cnt.map(|cnt| cnt > 0);
+            }
+        } else {
+            #[cfg(windows)]
+            let has = has_children_win32(&path).ok();
+            #[cfg(not(windows))]
+            let has = has_children(&path).ok();
+            item.has_children = has;
+        }
+    });
+}
+
+///
+/// fill `width`/`height`/`taken_at` of the image items of `p` in parallel
+///
+/// Items not reached within `DIMS_BUDGET` keep `None`; returns `false` then, so the listing
+/// isn't cached that way.
+pub fn update_dims(p: &str, items: &mut [Item], meta_types: &[MetaType]) -> bool {
+    if !meta_types.contains(&MetaType::Dim) {
+        return true;
+    }
+    let deadline = Instant::now() + DIMS_BUDGET;
+    let complete = AtomicBool::new(true);
+    items.par_iter_mut().filter(|item| !item.dir).for_each(|item| {
+        if Instant::now() > deadline {
+            complete.store(false, AtomicOrdering::Relaxed);
+            return;
+        }
+        if let Some((width, height, taken_at)) = read_dim(&PathBuf::from(p).join(&item.nm)) {
+            item.width = Some(width);
+            item.height = Some(height);
+            item.taken_at = taken_at;
+        }
+    });
+    complete.into_inner()
+}
+
+#[cfg(windows)]
 fn has_children_win32(path: &str) -> Result<bool> {
     let pattern: Vec<u16> = OsStr::new(&format!("{path}\\*"))
         .encode_wide()
@@ -239,6 +450,7 @@ fn has_children_win32(path: &str) -> Result<bool> {
             FIND_FIRST_EX_LARGE_FETCH,
         )?
     };
+    let _handle_guard = FindHandle(handle);
 
     let mut result = false;
 
@@ -265,10 +477,10 @@ fn get_ext(nm: &str) ->  Option<String> {
 }
 
 fn get_mime_type(nm: &str) -> Option<String> {
-    Some(from_path(&nm).first_or_octet_stream().to_string())
+    Some(from_path(nm).first_or_octet_stream().to_string())
 }
 
-pub fn update_items(items: &mut Vec<Item>, meta_types: &Vec<MetaType>) {
+pub fn update_items(items: &mut [Item], meta_types: &[MetaType]) {
     for item in items.iter_mut() {
         if meta_types.contains(&MetaType::Ext) {
             item.ext = get_ext(&item.nm)
@@ -279,7 +491,71 @@ pub fn update_items(items: &mut Vec<Item>, meta_types: &Vec<MetaType>) {
     }
 }
 
-pub fn update_max_len_nm(items: &Vec<Item>) -> usize {
+/// Applies `skip_n`/`take_n` and returns `(skip, take, sliced)`.
+pub fn slice_items(items: &[Item], skip_n: Option<usize>, take_n: Option<usize>) -> (usize, usize, Vec<Item>) {
+    let len_items = items.len();
+    let skip = cmp::min(skip_n.unwrap_or(0), len_items);
+    let take = match take_n {
+        Some(n) => cmp::min(n, len_items - skip),
+        None =>  len_items - skip
+    };
+    (skip, take, items.iter().skip(skip).take(take).cloned().collect())
+}
+
+///
+/// fill `items` of the directory entries of `path` down to `params.depth`
+///
+/// # arg
+/// - level: depth of `items` (the listed folder is 1)
+/// - ancestors: canonical paths of the directories above, a symlink/junction back into them is not followed
+/// - node_cnt: number of items in the tree so far, checked against `params.max_nodes`
+///
+/// The files of each level are filtered by `filter`, directories are kept so the walk goes on.
+/// Each level is sorted by `params.ordering` and sliced by `skip_n`/`take_n`.
+/// A directory that was not listed (depth, budget or cycle) keeps `items: None`.
+pub fn fill_tree(path: &Path, items: &mut [Item], params: &Params, filter: Option<&Filter>, level: usize, ancestors: &mut HashSet<PathBuf>, node_cnt: &mut usize) {
+    if level >= params.depth {
+        return;
+    }
+    for item in items.iter_mut().filter(|item| item.dir) {
+        if params.max_nodes.is_some_and(|max| *node_cnt >= max) {
+            return;
+        }
+        let sub_path = path.join(&item.nm);
+        let Ok(canonical) = sub_path.canonicalize() else {
+            continue;
+        };
+        if ancestors.contains(&canonical) {
+            continue;
+        }
+        let sub_path_str = sub_path.to_string_lossy();
+        let Ok(mut sub_items) = list_items(sub_path_str.as_ref(), &params.meta_types, filter, true) else {
+            continue;
+        };
+        update_dims(sub_path_str.as_ref(), &mut sub_items, &params.meta_types);
+        sort_items(&mut sub_items, &params.ordering);
+        let (_, _, mut sliced) = slice_items(&sub_items, params.skip_n, params.take_n);
+        if let Some(max) = params.max_nodes {
+            sliced.truncate(max - *node_cnt);
+        }
+        *node_cnt += sliced.len();
+        item.items = Some(sliced);
+    }
+    for item in items.iter_mut() {
+        let Some(sub_items) = item.items.as_mut() else {
+            continue;
+        };
+        let sub_path = path.join(&item.nm);
+        let Ok(canonical) = sub_path.canonicalize() else {
+            continue;
+        };
+        ancestors.insert(canonical.clone());
+        fill_tree(&sub_path, sub_items, params, filter, level + 1, ancestors, node_cnt);
+        ancestors.remove(&canonical);
+    }
+}
+
+pub fn update_max_len_nm(items: &[Item]) -> usize {
     let mut max_len: f32 = 0.0;
     for item in items {
         let a = item.nm.chars().count() as f32;
@@ -308,7 +584,7 @@ fn is_fullwidth(c: char) -> bool {
 
 
 fn cmp_item<T: Ord>(a: &T, b: &T, asc: &OrderAsc) -> Option<Ordering> {
-    if a.ne(&b) {
+    if a.ne(b) {
         return if asc == &OrderAsc::Asc {
             Some(a.cmp(b))
         } else {
@@ -318,47 +594,157 @@ fn cmp_item<T: Ord>(a: &T, b: &T, asc: &OrderAsc) -> Option<Ordering> {
     None
 }
 
-fn cmp_str_item(a: &String, b: &String, asc: &OrderAsc) -> Option<Ordering> {
+fn cmp_str_item(a: &str, b: &str, asc: &OrderAsc) -> Option<Ordering> {
     let a = a.to_lowercase();
     let b = b.to_lowercase();
     cmp_item(&a, &b, asc)
 }
 
-fn cmp_opt_str_item(a: &Option<String>, b: &Option<String>, asc: &OrderAsc) -> Option<Ordering> {
+/// `None` after every `Some` in both directions, so mixed lists still sort consistently
+fn cmp_opt_last<T: Ord>(a: &Option<T>, b: &Option<T>, asc: &OrderAsc) -> Option<Ordering> {
     match (a, b) {
-        (Some(a), Some(b)) => cmp_str_item(a, b, asc),
-        _ => None
+        (Some(a), Some(b)) => cmp_item(a, b, asc),
+        (Some(_), None) => Some(Ordering::Less),
+        (None, Some(_)) => Some(Ordering::Greater),
+        (None, None) => None,
     }
 }
 
-fn cmp_opt_item<T: Ord>(a: &Option<T>, b: &Option<T>, asc: &OrderAsc) -> Option<Ordering> {
-    match (a, b) {
-        (Some(a), Some(b)) => cmp_item(a, b, asc),
-        _ => None
+/// compare with digit runs as numbers, the rest lowercased
+pub fn cmp_natural(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(ca), Some(cb)) if ca.is_ascii_digit() && cb.is_ascii_digit() => {
+                let mut da = String::new();
+                while let Some(c) = a_chars.next_if(|c| c.is_ascii_digit()) {
+                    da.push(c);
+                }
+                let mut db = String::new();
+                while let Some(c) = b_chars.next_if(|c| c.is_ascii_digit()) {
+                    db.push(c);
+                }
+                let na = da.trim_start_matches('0');
+                let nb = db.trim_start_matches('0');
+                let ord = na.len().cmp(&nb.len())
+                    .then_with(|| na.cmp(nb))
+                    .then_with(|| da.len().cmp(&db.len()));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(ca), Some(cb)) => {
+                let ord = ca.to_lowercase().cmp(cb.to_lowercase());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
+fn new_collator(locale: &Option<String>) -> Option<Collator> {
+    let locale: Locale = match locale {
+        Some(tag) => tag.parse().ok()?,
+        None => Locale::UND,
+    };
+    let mut options = CollatorOptions::new();
+    options.numeric = Some(Numeric::On);
+    Collator::try_new(&(&locale).into(), options).ok()
+}
+
+/// string comparison of one `OrdItem`
+enum StrCmp {
+    Lower,
+    Natural,
+    Locale(Box<Collator>),
+    Case,
+}
+
+impl StrCmp {
+    fn new(ord: &OrdItem) -> Self {
+        match ord.cmp.clone().unwrap_or_default() {
+            OrderCmp::Lower => StrCmp::Lower,
+            OrderCmp::Natural => StrCmp::Natural,
+            OrderCmp::Locale => match new_collator(&ord.locale) {
+                Some(collator) => StrCmp::Locale(Box::new(collator)),
+                None => StrCmp::Lower,
+            },
+            OrderCmp::Case => StrCmp::Case,
+        }
+    }
+
+    fn cmp(&self, a: &str, b: &str, asc: &OrderAsc) -> Option<Ordering> {
+        let ord = match self {
+            StrCmp::Lower => return cmp_str_item(a, b, asc),
+            StrCmp::Natural => cmp_natural(a, b),
+            StrCmp::Locale(collator) => collator.compare(a, b),
+            StrCmp::Case => a.cmp(b),
+        };
+        match (ord, asc) {
+            (Ordering::Equal, _) => None,
+            (ord, OrderAsc::Asc) => Some(ord),
+            (ord, OrderAsc::Desc) => Some(ord.reverse()),
+        }
+    }
+
+    /// `None` last, like `cmp_opt_last`
+    fn cmp_opt(&self, a: Option<&String>, b: Option<&String>, asc: &OrderAsc) -> Option<Ordering> {
+        match (a, b) {
+            (Some(a), Some(b)) => self.cmp(a, b, asc),
+            (Some(_), None) => Some(Ordering::Less),
+            (None, Some(_)) => Some(Ordering::Greater),
+            (None, None) => None,
+        }
     }
 }
 
 
+/// `val` of files, folders have no extension/mime type to sort by
+fn file_only<'a>(item: &Item, val: &'a Option<String>) -> Option<&'a String> {
+    val.as_ref().filter(|_| !item.dir)
+}
 
-pub fn sort_items(items: &mut Vec<Item>, ordering: &Vec<OrdItem>) {
+/// Sorts by `ordering`, ties fall back to the lowercased name and then the exact name.
+/// Items without the value of a key (`None`) come after the others, ascending or descending.
+pub fn sort_items(items: &mut [Item], ordering: &[OrdItem]) {
+    let str_cmps: Vec<StrCmp> = ordering.iter().map(StrCmp::new).collect();
+    let pixels = |item: &Item| item.width.zip(item.height).map(|(w, h)| w as u64 * h as u64);
     items.sort_by(|a, b| {
-        for ord in ordering.iter() {
+        for (ord, str_cmp) in ordering.iter().zip(str_cmps.iter()) {
             let res = match ord.nm {
                 OrderBy::Dir => cmp_item(&b.dir, &a.dir, &ord.asc),
-                OrderBy::Nm => cmp_str_item(&a.nm, &b.nm, &ord.asc),
-                OrderBy::Ext if !a.dir => cmp_opt_str_item(&a.ext, &b.ext, &ord.asc),
-                OrderBy::Mt if !a.dir => cmp_opt_str_item(&a.mt, &b.mt, &ord.asc),
-                OrderBy::Sz if a.sz.ne(&b.sz)  => cmp_opt_item(&a.sz, &b.sz, &ord.asc),
-                _ => None,
+                OrderBy::Nm => str_cmp.cmp(&a.nm, &b.nm, &ord.asc),
+                OrderBy::Ext => str_cmp.cmp_opt(file_only(a, &a.ext), file_only(b, &b.ext), &ord.asc),
+                OrderBy::Mt => str_cmp.cmp_opt(file_only(a, &a.mt), file_only(b, &b.mt), &ord.asc),
+                OrderBy::Sz => cmp_opt_last(&a.sz, &b.sz, &ord.asc),
+                OrderBy::Tm => cmp_opt_last(&a.tm, &b.tm, &ord.asc),
+                OrderBy::Ctime => cmp_opt_last(&a.ctime, &b.ctime, &ord.asc),
+                OrderBy::Atime => cmp_opt_last(&a.atime, &b.atime, &ord.asc),
+                OrderBy::Mode => cmp_opt_last(&a.mode_oct, &b.mode_oct, &ord.asc),
+                OrderBy::Owner => str_cmp.cmp_opt(a.owner.as_ref(), b.owner.as_ref(), &ord.asc),
+                OrderBy::Group => str_cmp.cmp_opt(a.group.as_ref(), b.group.as_ref(), &ord.asc),
+                OrderBy::Hidden => cmp_opt_last(&a.hidden, &b.hidden, &ord.asc),
+                OrderBy::Readonly => cmp_opt_last(&a.readonly, &b.readonly, &ord.asc),
+                OrderBy::Symlink => str_cmp.cmp_opt(a.symlink.as_ref(), b.symlink.as_ref(), &ord.asc),
+                OrderBy::Inode => cmp_opt_last(&a.inode, &b.inode, &ord.asc),
+                OrderBy::Nlink => cmp_opt_last(&a.nlink, &b.nlink, &ord.asc),
+                OrderBy::ChildCnt => cmp_opt_last(&a.child_cnt, &b.child_cnt, &ord.asc),
+                OrderBy::Dim => cmp_opt_last(&pixels(a), &pixels(b), &ord.asc),
+                OrderBy::TakenAt => cmp_opt_last(&a.taken_at, &b.taken_at, &ord.asc),
             };
             if let Some(ord) = res {
                 return ord;
             }
         }
-        if !ordering.iter().any(|o| o.nm == OrderBy::Nm) {
-            return a.nm.cmp(&b.nm)
-        }
-        return Ordering::Equal
+        a.nm.to_lowercase().cmp(&b.nm.to_lowercase())
+            .then_with(|| a.nm.cmp(&b.nm))
     });
 }
 
@@ -368,14 +754,15 @@ pub fn sort_items(items: &mut Vec<Item>, ordering: &Vec<OrdItem>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    #[cfg(windows)]
     #[test]
     fn test_speed() {
         // let base_dir= r"C:\Windows\WinSxS";
         let base_dir= r"C:\";
         let meta_types = vec![MetaType::Sz, MetaType::Tm, MetaType::Ext, MetaType::Mt];
         let ordering = vec![
-            OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc},
-            OrdItem{nm: OrderBy::Tm, asc: OrderAsc::Asc},
+            OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc, cmp: None, locale: None},
+            OrdItem{nm: OrderBy::Tm, asc: OrderAsc::Asc, cmp: None, locale: None},
         ];
         let mut items = get_items_win32(base_dir, &meta_types).unwrap();
         update_items(&mut items, &meta_types);
@@ -383,6 +770,7 @@ mod tests {
     }
 
 
+    #[cfg(windows)]
     #[test]
     fn test_has_children_win32() {
         // let base_dir= r"C:\Windows\WinSxS";
@@ -394,6 +782,7 @@ mod tests {
         println!("{}", v.len());
     }
 
+    #[cfg(windows)]
     #[test]
     fn test_has_children() {
         // let base_dir= r"C:\Windows\WinSxS";
@@ -405,6 +794,7 @@ mod tests {
         println!("{}", v.len());
     }
 
+    #[cfg(windows)]
     #[test]
     fn test_get_paths_win32() {
         // let s = r"C:\Windows\WinSxS";
@@ -421,6 +811,7 @@ mod tests {
     }
 
 
+    #[cfg(windows)]
     #[test]
     fn test_get_paths() {
         // let s = r"C:\Windows\WinSxS";
@@ -436,10 +827,178 @@ mod tests {
         // assert!(api.get_paths(s).is_ok());
     }
 
+    #[cfg(windows)]
     #[test]
     fn test_get_full_path() {
         let s = r".";
         println!( "{:?}", get_full_path(s).unwrap());
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_read_items() {
+        let base_dir = std::env::temp_dir().join("napi-folder-test-read-items");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("sub")).unwrap();
+        std::fs::write(base_dir.join("a.TXT"), b"hello").unwrap();
+
+        let meta_types = vec![MetaType::Sz, MetaType::Tm, MetaType::Ext, MetaType::Mt];
+        let ordering = vec![
+            OrdItem{nm: OrderBy::Dir, asc: OrderAsc::Asc, cmp: None, locale: None},
+            OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc, cmp: None, locale: None},
+        ];
+        let mut items = read_items(base_dir.to_string_lossy().as_ref(), &meta_types).unwrap();
+        sort_items(&mut items, &ordering);
+        std::fs::remove_dir_all(&base_dir).unwrap();
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].nm, "sub");
+        assert!(items[0].dir);
+        assert_eq!(items[1].nm, "a.TXT");
+        assert!(!items[1].dir);
+        assert_eq!(items[1].sz, Some(5));
+        assert_eq!(items[1].ext.as_deref(), Some("txt"));
+        assert_eq!(items[1].mt.as_deref(), Some("text/plain"));
+        assert!(items[1].tm.is_some());
+    }
+
+    fn names(items: &[Item]) -> Vec<&str> {
+        items.iter().map(|item| item.nm.as_str()).collect()
+    }
+
+    fn sorted(nms: &[&str], cmp: OrderCmp, locale: Option<&str>) -> Vec<Item> {
+        let mut items: Vec<Item> = nms.iter().map(|nm| Item { nm: nm.to_string(), ..Item::default() }).collect();
+        let ordering = vec![OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc, cmp: Some(cmp), locale: locale.map(String::from)}];
+        sort_items(&mut items, &ordering);
+        items
+    }
+
+    #[test]
+    fn test_sort_cmp() {
+        let nms = ["img10.png", "Img2.png", "img1.png", "img02.png"];
+        assert_eq!(names(&sorted(&nms, OrderCmp::Lower, None)), ["img02.png", "img1.png", "img10.png", "Img2.png"]);
+        assert_eq!(names(&sorted(&nms, OrderCmp::Natural, None)), ["img1.png", "Img2.png", "img02.png", "img10.png"]);
+        assert_eq!(names(&sorted(&nms, OrderCmp::Case, None)), ["Img2.png", "img02.png", "img1.png", "img10.png"]);
+
+        let nms = ["f", "é", "E", "e"];
+        assert_eq!(names(&sorted(&nms, OrderCmp::Lower, None)), ["E", "e", "f", "é"]);
+        assert_eq!(names(&sorted(&nms, OrderCmp::Locale, Some("fr"))), ["e", "E", "é", "f"]);
+        assert_eq!(names(&sorted(&["나", "가", "다"], OrderCmp::Locale, Some("ko"))), ["가", "나", "다"]);
+    }
+
+    #[test]
+    fn test_sort_tm() {
+        let mut items: Vec<Item> = [("b", 2), ("a", 2), ("c", 1)].iter()
+            .map(|(nm, tm)| Item { nm: nm.to_string(), tm: Some(*tm), ..Item::default() })
+            .collect();
+        let ordering = vec![OrdItem{nm: OrderBy::Tm, asc: OrderAsc::Desc, cmp: None, locale: None}];
+        sort_items(&mut items, &ordering);
+        assert_eq!(names(&items), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn test_sort_none_last() {
+        let mut items: Vec<Item> = [("a", Some(3)), ("b", None), ("c", Some(1)), ("d", None), ("e", Some(2))].iter()
+            .map(|(nm, tm)| Item { nm: nm.to_string(), tm: *tm, ctime: *tm, owner: tm.map(|v| format!("u{}", v)), ..Item::default() })
+            .collect();
+        for nm in [OrderBy::Tm, OrderBy::Ctime, OrderBy::Owner] {
+            sort_items(&mut items, &[OrdItem{nm: nm.clone(), asc: OrderAsc::Asc, cmp: None, locale: None}]);
+            assert_eq!(names(&items), ["c", "e", "a", "b", "d"]);
+            sort_items(&mut items, &[OrdItem{nm, asc: OrderAsc::Desc, cmp: None, locale: None}]);
+            assert_eq!(names(&items), ["a", "e", "c", "b", "d"]);
+        }
+
+        // folders have no extension to sort by
+        let mut items: Vec<Item> = [("z.txt", false), ("b", true), ("a.zip", false), ("c.abc", false)].iter()
+            .map(|(nm, dir)| Item { nm: nm.to_string(), dir: *dir, ext: nm.rsplit_once('.').map(|(_, ext)| ext.to_string()), ..Item::default() })
+            .collect();
+        sort_items(&mut items, &[OrdItem{nm: OrderBy::Ext, asc: OrderAsc::Asc, cmp: None, locale: None}]);
+        assert_eq!(names(&items), ["c.abc", "z.txt", "a.zip", "b"]);
+    }
+
+    #[test]
+    fn test_sort_dim() {
+        let mut items: Vec<Item> = [("a.png", Some(100)), ("b.txt", None), ("c.png", Some(50))].iter()
+            .map(|(nm, w)| Item { nm: nm.to_string(), width: *w, height: w.map(|_| 1), ..Item::default() })
+            .collect();
+        for (asc, expected) in [(OrderAsc::Asc, ["c.png", "a.png", "b.txt"]), (OrderAsc::Desc, ["a.png", "c.png", "b.txt"])] {
+            sort_items(&mut items, &[OrdItem{nm: OrderBy::Dim, asc, cmp: None, locale: None}]);
+            assert_eq!(names(&items), expected);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_items_ext_meta() {
+        use std::os::unix::fs::PermissionsExt;
+        let base_dir = std::env::temp_dir().join("napi-folder-test-ext-meta");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(&base_dir).unwrap();
+        std::fs::write(base_dir.join("a.txt"), b"a").unwrap();
+        std::fs::set_permissions(base_dir.join("a.txt"), std::fs::Permissions::from_mode(0o444)).unwrap();
+        std::fs::write(base_dir.join(".hidden"), b"h").unwrap();
+        std::os::unix::fs::symlink("a.txt", base_dir.join("link")).unwrap();
+        std::os::unix::fs::symlink("missing", base_dir.join("broken")).unwrap();
+
+        let meta_types = vec![
+            MetaType::Ctime, MetaType::Atime, MetaType::Mode, MetaType::Owner, MetaType::Group,
+            MetaType::Hidden, MetaType::Readonly, MetaType::Symlink, MetaType::Inode, MetaType::Nlink,
+        ];
+        let items = read_items(base_dir.to_string_lossy().as_ref(), &meta_types).unwrap();
+        let get = |nm: &str| items.iter().find(|item| item.nm == nm).unwrap().clone();
+        std::fs::remove_dir_all(&base_dir).unwrap();
+
+        let a = get("a.txt");
+        assert_eq!(a.mode.as_deref(), Some("-r--r--r--"));
+        assert_eq!(a.mode_oct.as_deref(), Some("0444"));
+        assert_eq!(a.readonly, Some(true));
+        assert_eq!(a.hidden, Some(false));
+        assert_eq!(a.nlink, Some(1));
+        assert!(a.inode.is_some() && a.atime.is_some() && a.owner.is_some() && a.group.is_some());
+        assert!(a.symlink.is_none() && a.sz.is_none() && a.tm.is_none());
+        assert_eq!(get(".hidden").hidden, Some(true));
+
+        let link = get("link");
+        assert_eq!(link.symlink.as_deref(), Some("a.txt"));
+        assert_eq!(link.broken, Some(false));
+        assert_eq!(link.inode, a.inode);
+        assert!(link.mode.unwrap().starts_with('l'));
+        let broken = get("broken");
+        assert_eq!(broken.symlink.as_deref(), Some("missing"));
+        assert_eq!(broken.broken, Some(true));
+        assert!(broken.mode.unwrap().starts_with('l'));
+    }
+
+    #[test]
+    fn test_rwx_str() {
+        assert_eq!(crate::metadata_ext::rwx_str(0o755), "rwxr-xr-x");
+        assert_eq!(crate::metadata_ext::rwx_str(0o4755), "rwsr-xr-x");
+        assert_eq!(crate::metadata_ext::rwx_str(0o1777), "rwxrwxrwt");
+        assert_eq!(crate::metadata_ext::rwx_str(0o2640), "rw-r-S---");
+    }
+
+    #[test]
+    fn test_update_children() {
+        let base_dir = std::env::temp_dir().join("napi-folder-test-children");
+        let _ = std::fs::remove_dir_all(&base_dir);
+        std::fs::create_dir_all(base_dir.join("full").join("sub")).unwrap();
+        std::fs::create_dir_all(base_dir.join("empty")).unwrap();
+        std::fs::write(base_dir.join("full").join("a.txt"), b"a").unwrap();
+        std::fs::write(base_dir.join("f.txt"), b"f").unwrap();
+
+        let meta_types = vec![MetaType::ChildCnt, MetaType::HasChildren];
+        let items = read_items(base_dir.to_string_lossy().as_ref(), &meta_types).unwrap();
+        let get = |nm: &str| items.iter().find(|item| item.nm == nm).unwrap().clone();
+        let has_only = read_items(base_dir.to_string_lossy().as_ref(), &[MetaType::HasChildren]).unwrap();
+        std::fs::remove_dir_all(&base_dir).unwrap();
+
+        assert_eq!(get("full").child_cnt, Some(2));
+        assert_eq!(get("full").has_children, Some(true));
+        assert_eq!(get("empty").child_cnt, Some(0));
+        assert_eq!(get("empty").has_children, Some(false));
+        assert!(get("f.txt").child_cnt.is_none() && get("f.txt").has_children.is_none());
+        let full = has_only.iter().find(|item| item.nm == "full").unwrap();
+        assert_eq!(full.has_children, Some(true));
+        assert!(full.child_cnt.is_none());
+    }
+
+}
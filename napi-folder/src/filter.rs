@@ -0,0 +1,329 @@
+use std::time::SystemTime;
+use crate::models::{Item, MetaType, ApiError};
+use crate::system_time_ext::SystemTimeExt;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Cmp {
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Eq,
+}
+
+impl Cmp {
+    fn test(&self, a: u64, b: u64) -> bool {
+        match self {
+            Cmp::Lt => a < b,
+            Cmp::Le => a <= b,
+            Cmp::Gt => a > b,
+            Cmp::Ge => a >= b,
+            Cmp::Eq => a == b,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Term {
+    Ext(Vec<String>),
+    Size(Cmp, u64),
+    /// age of the item in seconds
+    Modified(Cmp, u64),
+    NameGlob(String),
+    NameContains(String),
+    Dir(bool),
+    Not(Box<Term>),
+}
+
+///
+/// filter expression for folder listings
+///
+/// Terms are separated by spaces and all of them must match. A leading `-` negates a term.
+///
+/// | term | matches |
+/// |------|---------|
+/// | `ext:jpg,png` | extension, case-insensitive |
+/// | `size>10M` | size with `< <= > >= =` and `B K M G T` units |
+/// | `modified<7d` | age with `s m h d w y` units (`modified<7d`: changed within 7 days) |
+/// | `name~"report*"` | name glob with `*` and `?`, case-insensitive |
+/// | `name:abc`, `abc` | name contains, case-insensitive |
+/// | `dir:false` | directories or files |
+///
+/// # Examples
+/// ```
+/// let filter = Filter::parse(r#"ext:jpg,png size>10M modified<7d name~"report*" dir:false"#)?;
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Result<Filter> {
+        let terms = tokenize(expr)?.iter()
+            .map(|token| parse_term(token))
+            .collect::<Result<Vec<Term>>>()?;
+        Ok(Filter { terms })
+    }
+
+    /// meta types the listing has to provide for `matches`
+    pub fn meta_types(&self) -> Vec<MetaType> {
+        let mut ret = vec![];
+        for term in self.terms.iter() {
+            let meta_type = match term_inner(term) {
+                Term::Size(..) => MetaType::Sz,
+                Term::Modified(..) => MetaType::Tm,
+                _ => continue,
+            };
+            if !ret.contains(&meta_type) {
+                ret.push(meta_type);
+            }
+        }
+        ret
+    }
+
+    pub fn matches(&self, item: &Item, now: u64) -> bool {
+        self.terms.iter().all(|term| match_term(term, item, now))
+    }
+
+    /// items matching the filter; with `keep_dirs` directories are kept as they are (trees)
+    pub fn apply(&self, items: Vec<Item>, keep_dirs: bool) -> Vec<Item> {
+        let now = SystemTime::now().to_sec();
+        items.into_iter().filter(|item| (keep_dirs && item.dir) || self.matches(item, now)).collect()
+    }
+
+    /// whether the result depends on the current time (`modified`), not only on the items
+    pub fn is_relative(&self) -> bool {
+        self.terms.iter().any(|term| matches!(term_inner(term), Term::Modified(..)))
+    }
+}
+
+fn term_inner(term: &Term) -> &Term {
+    match term {
+        Term::Not(inner) => term_inner(inner),
+        _ => term,
+    }
+}
+
+fn match_term(term: &Term, item: &Item, now: u64) -> bool {
+    match term {
+        Term::Ext(exts) => {
+            match item.nm.rsplit_once('.') {
+                Some((_, ext)) if !item.dir => exts.iter().any(|e| e.eq_ignore_ascii_case(ext)),
+                _ => false,
+            }
+        }
+        Term::Size(cmp, sz) => item.sz.is_some_and(|v| cmp.test(v, *sz)),
+        Term::Modified(cmp, age) => item.tm.is_some_and(|tm| cmp.test(now.saturating_sub(tm), *age)),
+        Term::NameGlob(pattern) => glob_match(pattern, &item.nm.to_lowercase()),
+        Term::NameContains(s) => item.nm.to_lowercase().contains(s.as_str()),
+        Term::Dir(dir) => item.dir == *dir,
+        Term::Not(inner) => !match_term(inner, item, now),
+    }
+}
+
+fn err(msg: &str, token: &str) -> ApiError {
+    ApiError::Folder(format!("Err Filter: {} `{}`", msg, token))
+}
+
+/// split by whitespace, `"..."` keeps spaces and the quotes are removed
+fn tokenize(expr: &str) -> Result<Vec<String>> {
+    let mut ret = vec![];
+    let mut cur = String::new();
+    let mut in_quote = false;
+    for c in expr.chars() {
+        match c {
+            '"' => in_quote = !in_quote,
+            c if c.is_whitespace() && !in_quote => {
+                if !cur.is_empty() {
+                    ret.push(std::mem::take(&mut cur));
+                }
+            }
+            c => cur.push(c),
+        }
+    }
+    if in_quote {
+        return Err(err("unclosed quote", expr));
+    }
+    if !cur.is_empty() {
+        ret.push(cur);
+    }
+    Ok(ret)
+}
+
+fn parse_term(token: &str) -> Result<Term> {
+    if let Some(rest) = token.strip_prefix('-') {
+        if !rest.is_empty() {
+            return Ok(Term::Not(Box::new(parse_term(rest)?)));
+        }
+    }
+    let Some(pos) = token.find([':', '~', '<', '>', '=']) else {
+        return Ok(Term::NameContains(token.to_lowercase()));
+    };
+    let key = &token[..pos];
+    let rest = &token[pos..];
+    let (op, val) = split_op(rest);
+    match (key.to_lowercase().as_str(), op) {
+        ("ext", ":") => {
+            let exts: Vec<String> = val.split(',')
+                .map(|s| s.trim_start_matches('.').to_lowercase())
+                .filter(|s| !s.is_empty())
+                .collect();
+            if exts.is_empty() {
+                return Err(err("empty ext", token));
+            }
+            Ok(Term::Ext(exts))
+        }
+        ("size" | "sz", op) if op != ":" && op != "~" => {
+            Ok(Term::Size(to_cmp(op), parse_size(val).ok_or_else(|| err("invalid size", token))?))
+        }
+        ("modified" | "tm", op) if op != ":" && op != "~" => {
+            Ok(Term::Modified(to_cmp(op), parse_age(val).ok_or_else(|| err("invalid age", token))?))
+        }
+        ("name" | "nm", "~") => Ok(Term::NameGlob(val.to_lowercase())),
+        ("name" | "nm", ":") => Ok(Term::NameContains(val.to_lowercase())),
+        ("dir", ":") => match val.to_lowercase().as_str() {
+            "true" | "yes" | "1" => Ok(Term::Dir(true)),
+            "false" | "no" | "0" => Ok(Term::Dir(false)),
+            _ => Err(err("invalid bool", token)),
+        },
+        _ => Err(err("unknown term", token)),
+    }
+}
+
+fn split_op(s: &str) -> (&str, &str) {
+    for op in ["<=", ">=", "<", ">", "=", ":", "~"] {
+        if let Some(val) = s.strip_prefix(op) {
+            return (op, val);
+        }
+    }
+    ("", s)
+}
+
+fn to_cmp(op: &str) -> Cmp {
+    match op {
+        "<" => Cmp::Lt,
+        "<=" => Cmp::Le,
+        ">" => Cmp::Gt,
+        ">=" => Cmp::Ge,
+        _ => Cmp::Eq,
+    }
+}
+
+fn split_num(s: &str) -> Option<(f64, String)> {
+    let pos = s.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(s.len());
+    let num: f64 = s[..pos].parse().ok()?;
+    Some((num, s[pos..].to_lowercase()))
+}
+
+/// `10`, `10B`, `1.5K`, `10M`, `10MB`, `2G`, `1T` (1024 based)
+fn parse_size(s: &str) -> Option<u64> {
+    let (num, unit) = split_num(s)?;
+    let mul: u64 = match unit.trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        _ => return None,
+    };
+    Some((num * mul as f64) as u64)
+}
+
+/// `30s`, `10m`, `2h`, `7d`, `2w`, `1y`
+fn parse_age(s: &str) -> Option<u64> {
+    let (num, unit) = split_num(s)?;
+    let mul: u64 = match unit.as_str() {
+        "s" => 1,
+        "m" | "min" => 60,
+        "h" => 60 * 60,
+        "" | "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        "y" => 365 * 24 * 60 * 60,
+        _ => return None,
+    };
+    Some((num * mul as f64) as u64)
+}
+
+/// `*` matches any run of chars, `?` one char
+pub fn glob_match(pattern: &str, s: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let s: Vec<char> = s.chars().collect();
+    let (mut pi, mut si) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while si < s.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == s[si]) {
+            pi += 1;
+            si += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            star = Some((pi, si));
+            pi += 1;
+        } else if let Some((star_pi, star_si)) = star {
+            pi = star_pi + 1;
+            si = star_si + 1;
+            star = Some((star_pi, star_si + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(nm: &str, dir: bool, sz: u64, tm: u64) -> Item {
+        Item {
+            nm: nm.to_string(),
+            dir,
+            sz: Some(sz),
+            tm: Some(tm),
+            ..Item::default()
+        }
+    }
+
+    #[test]
+    fn test_filter() {
+        let now = 1_000_000_000;
+        let day = 24 * 60 * 60;
+        let filter = Filter::parse(r#"ext:jpg,PNG size>10M modified<7d name~"report*" dir:false"#).unwrap();
+        assert_eq!(filter.meta_types(), vec![MetaType::Sz, MetaType::Tm]);
+        assert!(filter.matches(&item("Report 1.JPG", false, 11 << 20, now - day), now));
+        assert!(!filter.matches(&item("report.gif", false, 11 << 20, now - day), now));
+        assert!(!filter.matches(&item("report.png", false, 9 << 20, now - day), now));
+        assert!(!filter.matches(&item("report.png", false, 11 << 20, now - 8 * day), now));
+        assert!(!filter.matches(&item("my report.png", false, 11 << 20, now - day), now));
+        assert!(!filter.matches(&item("report.png", true, 11 << 20, now - day), now));
+
+        assert!(filter.is_relative());
+        let items = vec![item("docs", true, 0, now), item("a.jpg", false, 0, now)];
+        assert_eq!(filter.apply(items.clone(), true).len(), 1);
+        assert!(filter.apply(items, false).is_empty());
+
+        let filter = Filter::parse("-ext:tmp abc").unwrap();
+        assert!(filter.matches(&item("xABCx.txt", false, 0, 0), now));
+        assert!(!filter.matches(&item("abc.tmp", false, 0, 0), now));
+        assert!(!filter.is_relative());
+    }
+
+    #[test]
+    fn test_filter_err() {
+        assert!(Filter::parse("size>abc").is_err());
+        assert!(Filter::parse("foo:bar").is_err());
+        assert!(Filter::parse(r#"name~"abc"#).is_err());
+        assert!(Filter::parse("dir:maybe").is_err());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("report*", "report 2024.pdf"));
+        assert!(glob_match("*.p?f", "a.pdf"));
+        assert!(glob_match("a*b*c", "aXXbYYc"));
+        assert!(!glob_match("a*b*c", "aXXbYY"));
+        assert!(glob_match("*", ""));
+    }
+}
@@ -0,0 +1,316 @@
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+use rayon::prelude::*;
+use crate::models::{ApiError, FindHit, FindMode, MetaType};
+use crate::dir::read_items;
+use crate::filter::glob_match;
+use crate::search::Matcher;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// `FindMode` with its compiled query, shared by `Finder` and the name index
+pub enum Query {
+    /// lowercased, against the relative path if it has a `/`, else the name
+    Glob(String),
+    Regex(Matcher),
+    Fuzzy(Box<SkimMatcherV2>, String),
+    /// lowercased
+    Substring(String),
+}
+
+impl Query {
+    pub fn new(query: &str, mode: FindMode) -> Result<Self> {
+        if query.is_empty() {
+            return Err(ApiError::Folder(String::from("Err Find: empty query")));
+        }
+        Ok(match mode {
+            FindMode::Glob => Query::Glob(query.to_lowercase()),
+            FindMode::Regex => Query::Regex(Matcher::new(query, true, false)?),
+            FindMode::Fuzzy => Query::Fuzzy(Box::new(SkimMatcherV2::default().smart_case()), query.to_string()),
+            FindMode::Substring => Query::Substring(query.to_lowercase()),
+        })
+    }
+
+    /// score and highlighted ranges of `rel`, in UTF-16 code units like JS string indices
+    pub fn match_rel(&self, nm: &str, rel: &str) -> Option<(i64, Vec<(usize, usize)>)> {
+        match self {
+            Query::Glob(pattern) => {
+                let rel_len = utf16_len(rel);
+                if pattern.contains('/') {
+                    glob_match(pattern, &rel.to_lowercase()).then(|| (0, vec![(0, rel_len)]))
+                } else {
+                    glob_match(pattern, &nm.to_lowercase()).then(|| (0, vec![(rel_len - utf16_len(nm), rel_len)]))
+                }
+            }
+            Query::Regex(matcher) => {
+                let ranges = to_utf16_ranges(rel, matcher.find_ranges(rel));
+                (!ranges.is_empty()).then_some((0, ranges))
+            }
+            Query::Fuzzy(matcher, pattern) => {
+                let (score, indices) = matcher.fuzzy_indices(rel, pattern)?;
+                let offsets: Vec<(usize, usize)> = rel.char_indices().map(|(i, ch)| (i, i + ch.len_utf8())).collect();
+                let runs = to_ranges(&indices).into_iter()
+                    .map(|(start, end)| (offsets[start].0, offsets[end - 1].1))
+                    .collect();
+                Some((score, to_utf16_ranges(rel, runs)))
+            }
+            Query::Substring(pattern) => {
+                let lower = rel.to_lowercase();
+                let ranges = to_utf16_ranges(&lower, lower.match_indices(pattern.as_str())
+                    .map(|(start, m)| (start, start + m.len()))
+                    .collect());
+                (!ranges.is_empty()).then_some((0, ranges))
+            }
+        }
+    }
+}
+
+/// best first: fuzzy by score, then by shortest relative path
+fn cmp_rank(a: &FindHit, b: &FindHit) -> CmpOrdering {
+    b.score.cmp(&a.score)
+        .then_with(|| a.rel.len().cmp(&b.rel.len()))
+        .then_with(|| a.rel.cmp(&b.rel))
+}
+
+/// `FindHit` ordered by `cmp_rank`, the worst is the greatest
+struct Ranked(FindHit);
+
+impl PartialEq for Ranked {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == CmpOrdering::Equal
+    }
+}
+
+impl Eq for Ranked {}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        cmp_rank(&self.0, &other.0)
+    }
+}
+
+///
+/// the best `max` of the hits pushed so far, `total` counts all of them
+///
+/// A heap with the worst kept hit on top, so a better one replaces it.
+pub struct TopHits {
+    max: usize,
+    heap: BinaryHeap<Ranked>,
+    pub total: usize,
+}
+
+impl TopHits {
+    pub fn new(max: usize) -> Self {
+        TopHits { max, heap: BinaryHeap::new(), total: 0 }
+    }
+
+    pub fn push(&mut self, hit: FindHit) {
+        self.total += 1;
+        if self.heap.len() < self.max {
+            self.heap.push(Ranked(hit));
+        } else if let Some(mut worst) = self.heap.peek_mut() {
+            if cmp_rank(&hit, &worst.0) == CmpOrdering::Less {
+                *worst = Ranked(hit);
+            }
+        }
+    }
+
+    pub fn merge(mut self, other: TopHits) -> Self {
+        let total = self.total + other.total;
+        for hit in other.heap {
+            self.push(hit.0);
+        }
+        self.total = total;
+        self
+    }
+
+    /// the kept hits best first, and `total`
+    pub fn into_ranked(self) -> (Vec<FindHit>, usize) {
+        (self.heap.into_sorted_vec().into_iter().map(|hit| hit.0).collect(), self.total)
+    }
+}
+
+///
+/// file name search over a folder tree with `read_items`, folders in parallel
+///
+/// Hits of each folder go to `on_hits` as soon as it is listed, `run` returns the best `max_results`
+/// of them ranked and the count of all: fuzzy by score, glob and regex by shortest relative path.
+/// Hidden entries are skipped unless `hidden`.
+pub struct Finder<'a> {
+    query: Query,
+    pub hidden: bool,
+    pub meta_types: Vec<MetaType>,
+    pub max_results: usize,
+    pub cancel: &'a AtomicBool,
+    pub on_hits: &'a (dyn Fn(Vec<FindHit>) + Sync),
+    hits: Mutex<TopHits>,
+}
+
+impl<'a> Finder<'a> {
+    pub fn new(query: &str, mode: FindMode, cancel: &'a AtomicBool, on_hits: &'a (dyn Fn(Vec<FindHit>) + Sync)) -> Result<Self> {
+        Ok(Finder {
+            query: Query::new(query, mode)?,
+            hidden: false,
+            meta_types: vec![MetaType::Sz, MetaType::Tm],
+            max_results: usize::MAX,
+            cancel,
+            on_hits,
+            hits: Mutex::new(TopHits::new(0)),
+        })
+    }
+
+    /// the best `max_results` hits and the count of all
+    pub fn run(mut self, root: &Path) -> (Vec<FindHit>, usize) {
+        self.hits = Mutex::new(TopHits::new(self.max_results));
+        self.find_dir(root, "");
+        self.hits.into_inner().map(TopHits::into_ranked).unwrap_or_default()
+    }
+
+    fn find_dir(&self, dir: &Path, rel: &str) {
+        if self.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(items) = read_items(&dir.to_string_lossy(), &self.meta_types) else {
+            return;
+        };
+        let mut hits = vec![];
+        let mut sub_dirs = vec![];
+        for item in items {
+            if !self.hidden && item.nm.starts_with('.') {
+                continue;
+            }
+            let sub_rel = if rel.is_empty() { item.nm.clone() } else { format!("{}/{}", rel, item.nm) };
+            // `item.symlink` is only set with `MetaType::Symlink`, the link itself is checked
+            let sub_dir = dir.join(&item.nm);
+            if item.dir && !sub_dir.is_symlink() {
+                sub_dirs.push((sub_dir, sub_rel.clone()));
+            }
+            if let Some((score, hl)) = self.query.match_rel(&item.nm, &sub_rel) {
+                hits.push(FindHit { rel: sub_rel, score, hl, item });
+            }
+        }
+        if !hits.is_empty() {
+            (self.on_hits)(hits.clone());
+            if let Ok(mut top) = self.hits.lock() {
+                hits.into_iter().for_each(|hit| top.push(hit));
+            }
+        }
+        sub_dirs.par_iter().for_each(|(sub_dir, sub_rel)| self.find_dir(sub_dir, sub_rel));
+    }
+}
+
+/// sorted char indices to `(start, end)` runs
+fn to_ranges(indices: &[usize]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = vec![];
+    for &i in indices {
+        match ranges.last_mut() {
+            Some(last) if last.1 == i => last.1 = i + 1,
+            _ => ranges.push((i, i + 1)),
+        }
+    }
+    ranges
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+/// byte ranges of `s` to UTF-16 code unit ranges
+fn to_utf16_ranges(s: &str, ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    ranges.into_iter()
+        .map(|(start, end)| {
+            let start_u16 = utf16_len(&s[..start]);
+            (start_u16, start_u16 + utf16_len(&s[start..end]))
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find(base: &Path, query: &str, mode: FindMode) -> Vec<(String, Vec<(usize, usize)>)> {
+        let cancel = AtomicBool::new(false);
+        let on_hits = |_| {};
+        let finder = Finder::new(query, mode, &cancel, &on_hits).unwrap();
+        finder.run(base).0.into_iter().map(|hit| (hit.rel, hit.hl)).collect()
+    }
+
+    #[test]
+    fn test_find() {
+        let base = std::env::temp_dir().join("napi-folder-test-find");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("src/api")).unwrap();
+        std::fs::create_dir_all(base.join(".git")).unwrap();
+        std::fs::write(base.join("src/api/main.rs"), "").unwrap();
+        std::fs::write(base.join("src/lib.rs"), "").unwrap();
+        std::fs::write(base.join("README.md"), "").unwrap();
+        std::fs::write(base.join(".git/config.rs"), "").unwrap();
+
+        assert_eq!(find(&base, "*.RS", FindMode::Glob), vec![
+            (String::from("src/lib.rs"), vec![(4, 10)]),
+            (String::from("src/api/main.rs"), vec![(8, 15)]),
+        ]);
+        assert_eq!(find(&base, "src/*/*.rs", FindMode::Glob).len(), 1);
+        assert_eq!(find(&base, r"a\w+n", FindMode::Regex), vec![(String::from("src/api/main.rs"), vec![(9, 12)])]);
+
+        let hits = find(&base, "sam", FindMode::Fuzzy);
+        assert_eq!(hits[0], (String::from("src/api/main.rs"), vec![(0, 1), (4, 5), (8, 9)]));
+        assert!(find(&base, "zzz", FindMode::Fuzzy).is_empty());
+        assert_eq!(find(&base, "API", FindMode::Substring), vec![
+            (String::from("src/api"), vec![(4, 7)]),
+            (String::from("src/api/main.rs"), vec![(4, 7)]),
+        ]);
+
+        // a link back up is listed but not entered
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("..", base.join("src/api/loop")).unwrap();
+            assert_eq!(find(&base, "main", FindMode::Substring).len(), 1);
+            assert_eq!(find(&base, "loop", FindMode::Substring), vec![(String::from("src/api/loop"), vec![(8, 12)])]);
+        }
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_top_hits() {
+        let hit = |rel: &str, score: i64| FindHit { rel: rel.to_string(), score, ..FindHit::default() };
+        let mut top = TopHits::new(2);
+        for (rel, score) in [("a/b", 0), ("c", 0), ("dd", 0), ("e/f/g", 9)] {
+            top.push(hit(rel, score));
+        }
+        let mut other = TopHits::new(2);
+        other.push(hit("b", 0));
+        let (hits, total) = top.merge(other).into_ranked();
+        assert_eq!(hits.iter().map(|hit| hit.rel.as_str()).collect::<Vec<_>>(), vec!["e/f/g", "b"]);
+        assert_eq!(total, 5);
+    }
+
+    #[test]
+    fn test_match_rel_utf16() {
+        // `😀` is one char but two UTF-16 code units
+        let query = Query::new("x", FindMode::Substring).unwrap();
+        assert_eq!(query.match_rel("😀x", "é/😀x"), Some((0, vec![(4, 5)])));
+        let query = Query::new("*.rs", FindMode::Glob).unwrap();
+        assert_eq!(query.match_rel("😀.rs", "é/😀.rs"), Some((0, vec![(2, 7)])));
+        let query = Query::new("ex", FindMode::Fuzzy).unwrap();
+        assert_eq!(query.match_rel("😀x", "e😀x").unwrap().1, vec![(0, 1), (3, 4)]);
+    }
+
+    #[test]
+    fn test_to_ranges() {
+        assert_eq!(to_ranges(&[0, 1, 2, 5, 7, 8]), vec![(0, 3), (5, 6), (7, 9)]);
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write;
+use std::io::{Read, Seek, SeekFrom};
+use memchr::memmem;
+use crate::models::{ApiError, HexPage, HexRow};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+pub const DEFAULT_BYTES_PER_ROW: usize = 16;
+pub const MAX_BYTES_PER_ROW: usize = 64;
+/// bytes of a `read_hex` page if no length is given, and the most it returns
+pub const DEFAULT_HEX_LEN: usize = 4 * 1024;
+pub const MAX_HEX_LEN: usize = 256 * 1024;
+const CHUNK_SZ: usize = 1024 * 1024;
+
+///
+/// rows of offset/hex/ASCII of up to `length` bytes of `file`
+///
+/// `offset` is moved back to the start of its row so rows line up across pages.
+pub fn read_hex<R: Read + Seek>(file: &mut R, sz: u64, offset: u64, length: usize, bytes_per_row: usize) -> Result<HexPage> {
+    let bytes_per_row = bytes_per_row.clamp(1, MAX_BYTES_PER_ROW);
+    let offset = offset.min(sz) / bytes_per_row as u64 * bytes_per_row as u64;
+    let length = length.clamp(1, MAX_HEX_LEN) as u64;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = vec![];
+    file.take(length).read_to_end(&mut buf)?;
+    let rows = buf.chunks(bytes_per_row).enumerate()
+        .map(|(i, row)| to_row(offset + (i * bytes_per_row) as u64, row))
+        .collect();
+    let next_offset = offset + buf.len() as u64;
+    Ok(HexPage {
+        sz,
+        offset,
+        next_offset,
+        bytes_per_row,
+        rows,
+        eof: next_offset >= sz,
+        ..HexPage::default()
+    })
+}
+
+fn to_row(offset: u64, bytes: &[u8]) -> HexRow {
+    let mut hex = String::with_capacity(bytes.len() * 3);
+    for (i, b) in bytes.iter().enumerate() {
+        if i > 0 {
+            hex.push(' ');
+        }
+        let _ = write!(hex, "{:02x}", b);
+    }
+    let ascii = bytes.iter()
+        .map(|&b| if (0x20..0x7f).contains(&b) { b as char } else { '.' })
+        .collect();
+    HexRow { offset, hex, ascii }
+}
+
+///
+/// bytes of a `find_bytes` pattern
+///
+/// With `hex`, pairs of hex digits, spaces allowed: `de ad BE EF`; else the UTF-8 bytes of `pattern`.
+pub fn parse_pattern(pattern: &str, hex: bool) -> Result<Vec<u8>> {
+    let bytes = if hex {
+        let digits: Vec<u8> = pattern.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+        if !digits.len().is_multiple_of(2) {
+            return Err(ApiError::Folder(format!("Err Hex: odd number of digits `{}`", pattern)));
+        }
+        digits.chunks(2)
+            .map(|pair| std::str::from_utf8(pair).ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| ApiError::Folder(format!("Err Hex: not a hex byte `{}`", String::from_utf8_lossy(pair)))))
+            .collect::<Result<Vec<u8>>>()?
+    } else {
+        pattern.as_bytes().to_vec()
+    };
+    if bytes.is_empty() {
+        return Err(ApiError::Folder(String::from("Err Hex: empty pattern")));
+    }
+    Ok(bytes)
+}
+
+///
+/// offsets of `pattern` in `reader` from `start`, read in chunks
+///
+/// Matches don't overlap. Stops after `max_hits`; the second value is `true` if there were more.
+pub fn find_bytes<R: Read + Seek>(reader: &mut R, pattern: &[u8], start: u64, max_hits: usize) -> Result<(Vec<u64>, bool)> {
+    let finder = memmem::Finder::new(pattern);
+    let keep = pattern.len() - 1;
+    reader.seek(SeekFrom::Start(start))?;
+    let mut hits = vec![];
+    // `buf` holds the bytes from `buf_offset`, the first `keep` of them from the previous chunk
+    let mut buf: Vec<u8> = Vec::with_capacity(CHUNK_SZ + keep);
+    let mut buf_offset = start;
+    // matches before this position in `buf` were already reported
+    let mut from = 0;
+    loop {
+        let filled = buf.len();
+        buf.resize(filled + CHUNK_SZ, 0);
+        let n = reader.read(&mut buf[filled..])?;
+        buf.truncate(filled + n);
+        if n == 0 {
+            return Ok((hits, false));
+        }
+        let base = from;
+        for pos in finder.find_iter(&buf[base..]) {
+            if hits.len() == max_hits {
+                return Ok((hits, true));
+            }
+            hits.push(buf_offset + (base + pos) as u64);
+            from = base + pos + pattern.len();
+        }
+        let cut = buf.len() - keep.min(buf.len());
+        buf_offset += cut as u64;
+        from = from.saturating_sub(cut);
+        buf.drain(..cut);
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_read_hex() {
+        let data: Vec<u8> = (0u8..40).chain(b"Hi!".iter().cloned()).collect();
+        let page = read_hex(&mut Cursor::new(&data), data.len() as u64, 20, 24, 16).unwrap();
+        assert_eq!((page.offset, page.next_offset, page.eof), (16, 40, false));
+        assert_eq!(page.rows.len(), 2);
+        assert_eq!(page.rows[0].offset, 16);
+        assert!(page.rows[0].hex.starts_with("10 11 12"));
+        assert_eq!(page.rows[0].ascii, ".".repeat(16));
+
+        let page = read_hex(&mut Cursor::new(&data), data.len() as u64, 40, 100, 8).unwrap();
+        assert_eq!(page.rows.len(), 1);
+        assert_eq!(page.rows[0].hex, "48 69 21");
+        assert_eq!(page.rows[0].ascii, "Hi!");
+        assert!(page.eof);
+    }
+
+    #[test]
+    fn test_parse_pattern() {
+        assert_eq!(parse_pattern("de ad BE EF", true).unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(parse_pattern("PK", false).unwrap(), b"PK".to_vec());
+        assert!(parse_pattern("abc", true).is_err());
+        assert!(parse_pattern("zz", true).is_err());
+        assert!(parse_pattern(" ", true).is_err());
+    }
+
+    #[test]
+    fn test_find_bytes() {
+        // a match across the chunk boundary, and the pattern at the end
+        let mut data = vec![0u8; CHUNK_SZ - 2];
+        data.extend_from_slice(b"needle");
+        data.extend(vec![1u8; 100]);
+        data.extend_from_slice(b"needle");
+        let (hits, more) = find_bytes(&mut Cursor::new(&data), b"needle", 0, 10).unwrap();
+        assert_eq!(hits, vec![(CHUNK_SZ - 2) as u64, (CHUNK_SZ + 104) as u64]);
+        assert!(!more);
+
+        let (hits, more) = find_bytes(&mut Cursor::new(&data), b"needle", CHUNK_SZ as u64, 10).unwrap();
+        assert_eq!(hits, vec![(CHUNK_SZ + 104) as u64]);
+        assert!(!more);
+
+        let (hits, more) = find_bytes(&mut Cursor::new(b"aaaaa"), b"aa", 0, 1).unwrap();
+        assert_eq!(hits, vec![0]);
+        assert!(more);
+        let (hits, _) = find_bytes(&mut Cursor::new(b"aaaaa"), b"aa", 0, 10).unwrap();
+        assert_eq!(hits, vec![0, 2]);
+    }
+}
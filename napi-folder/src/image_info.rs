@@ -0,0 +1,266 @@
+use std::fs::File;
+use std::io::{BufReader, Seek, SeekFrom};
+use std::path::Path;
+use exif::{Exif, In, Tag, Value};
+use image::{ImageDecoder, ImageFormat, ImageReader};
+use crate::models::{ApiError, ExifInfo, GpsPos, ImageInfo};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+///
+/// size, color type, frames and EXIF of an image, without decoding its pixels
+///
+/// Formats are told by their signature, not the extension.
+pub fn read_image_info(path: &Path) -> Result<ImageInfo> {
+    let err = |err: image::ImageError| ApiError::Folder(format!("Err ImageInfo: {}", err));
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let Some(format) = reader.format() else {
+        return Err(ApiError::Folder(format!("Err ImageInfo: unknown format `{}`", path.to_string_lossy())));
+    };
+    let decoder = reader.into_decoder().map_err(err)?;
+    let (width, height) = decoder.dimensions();
+    let color_type = decoder.original_color_type();
+    let mut file = BufReader::new(File::open(path)?);
+    let frames = count_frames(&mut file, format);
+    file.seek(SeekFrom::Start(0))?;
+    let exif = exif::Reader::new().read_from_container(&mut file).ok().map(|exif| to_exif_info(&exif));
+    Ok(ImageInfo {
+        path: path.to_string_lossy().into_owned(),
+        format: format.extensions_str().first().unwrap_or(&"").to_string(),
+        width,
+        height,
+        color_type: format!("{:?}", color_type),
+        bit_depth: (color_type.bits_per_pixel() / color_type.channel_count().max(1) as u16) as u8,
+        frames,
+        exif,
+    })
+}
+
+///
+/// `(width, height, taken_at)` for `MetaType::Dim`, `None` if `path` isn't a readable image
+///
+/// Only the header and the EXIF block are read.
+pub fn read_dim(path: &Path) -> Option<(u32, u32, Option<String>)> {
+    ImageFormat::from_path(path).ok().filter(|format| format.reading_enabled())?;
+    let (width, height) = ImageReader::open(path).ok()?
+        .with_guessed_format().ok()?
+        .into_dimensions().ok()?;
+    let mut file = BufReader::new(File::open(path).ok()?);
+    let taken_at = exif::Reader::new().read_from_container(&mut file).ok()
+        .and_then(|exif| taken_at(&exif));
+    Some((width, height, taken_at))
+}
+
+/// frames of an animated GIF, APNG or WebP; `None` for still images
+fn count_frames(file: &mut BufReader<File>, format: ImageFormat) -> Option<u32> {
+    let frames = match format {
+        ImageFormat::Gif => {
+            let mut opt = gif::DecodeOptions::new();
+            opt.skip_frame_decoding(true);
+            let mut decoder = opt.read_info(file).ok()?;
+            let mut cnt = 0;
+            while let Ok(Some(_)) = decoder.next_frame_info() {
+                cnt += 1;
+            }
+            cnt
+        }
+        ImageFormat::Png => png::Decoder::new(file).read_info().ok()?
+            .info().animation_control?
+            .num_frames,
+        ImageFormat::WebP => image_webp::WebPDecoder::new(file).ok()
+            .filter(|decoder| decoder.is_animated())?
+            .num_frames(),
+        _ => return None,
+    };
+    (frames > 1).then_some(frames)
+}
+
+fn to_exif_info(exif: &Exif) -> ExifInfo {
+    ExifInfo {
+        make: ascii(exif, Tag::Make),
+        model: ascii(exif, Tag::Model),
+        lens: ascii(exif, Tag::LensModel),
+        exposure_time: rational(exif, Tag::ExposureTime, 0),
+        f_number: rational(exif, Tag::FNumber, 0),
+        iso: uint(exif, Tag::PhotographicSensitivity),
+        focal_length: rational(exif, Tag::FocalLength, 0),
+        taken_at: taken_at(exif),
+        orientation: uint(exif, Tag::Orientation).map(|v| v as u16),
+        gps: gps(exif),
+    }
+}
+
+fn ascii(exif: &Exif, tag: Tag) -> Option<String> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Ascii(values) => values.first()
+            .map(|v| String::from_utf8_lossy(v).trim_matches(|c: char| c == '\0' || c.is_whitespace()).to_string())
+            .filter(|s| !s.is_empty()),
+        _ => None,
+    }
+}
+
+fn uint(exif: &Exif, tag: Tag) -> Option<u32> {
+    exif.get_field(tag, In::PRIMARY)?.value.get_uint(0)
+}
+
+fn rational(exif: &Exif, tag: Tag, idx: usize) -> Option<f64> {
+    match &exif.get_field(tag, In::PRIMARY)?.value {
+        Value::Rational(values) => values.get(idx).filter(|v| v.denom != 0).map(|v| v.to_f64()),
+        Value::SRational(values) => values.get(idx).filter(|v| v.denom != 0).map(|v| v.to_f64()),
+        _ => None,
+    }
+}
+
+/// `DateTimeOriginal`, else `DateTime`, as `2024-05-01T12:34:56[+09:00]`
+fn taken_at(exif: &Exif) -> Option<String> {
+    let (tag, offset_tag) = [(Tag::DateTimeOriginal, Tag::OffsetTimeOriginal), (Tag::DateTime, Tag::OffsetTime)]
+        .into_iter()
+        .find(|(tag, _)| exif.get_field(*tag, In::PRIMARY).is_some())?;
+    let Value::Ascii(values) = &exif.get_field(tag, In::PRIMARY)?.value else {
+        return None;
+    };
+    let mut dt = exif::DateTime::from_ascii(values.first()?).ok()?;
+    if let Some(Value::Ascii(values)) = exif.get_field(offset_tag, In::PRIMARY).map(|field| &field.value) {
+        let _ = values.first().map(|v| dt.parse_offset(v));
+    }
+    let mut s = format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}", dt.year, dt.month, dt.day, dt.hour, dt.minute, dt.second);
+    if let Some(offset) = dt.offset {
+        let sign = if offset < 0 { '-' } else { '+' };
+        s.push_str(&format!("{}{:02}:{:02}", sign, offset.abs() / 60, offset.abs() % 60));
+    }
+    Some(s)
+}
+
+fn gps(exif: &Exif) -> Option<GpsPos> {
+    let degrees = |tag: Tag, ref_tag: Tag, negative: &str| -> Option<f64> {
+        let deg = rational(exif, tag, 0)? + rational(exif, tag, 1).unwrap_or(0.0) / 60.0 + rational(exif, tag, 2).unwrap_or(0.0) / 3600.0;
+        Some(if ascii(exif, ref_tag).is_some_and(|r| r.eq_ignore_ascii_case(negative)) { -deg } else { deg })
+    };
+    let alt = rational(exif, Tag::GPSAltitude, 0)
+        .map(|alt| if uint(exif, Tag::GPSAltitudeRef) == Some(1) { -alt } else { alt });
+    Some(GpsPos {
+        lat: degrees(Tag::GPSLatitude, Tag::GPSLatitudeRef, "S")?,
+        lon: degrees(Tag::GPSLongitude, Tag::GPSLongitudeRef, "W")?,
+        alt,
+    })
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// little-endian TIFF block of a primary, an Exif and a GPS IFD
+    fn tiff_exif() -> Vec<u8> {
+        // (tag, type, count, value or offset) with values that don't fit in 4 bytes appended after the IFDs
+        let mut out: Vec<u8> = b"II*\0\x08\0\0\0".to_vec();
+        let mut data: Vec<u8> = vec![];
+        let ifd0: Vec<(u16, u16, Vec<u8>)> = vec![
+            (0x010f, 2, b"Canon\0".to_vec()),
+            (0x0110, 2, b"EOS R5\0".to_vec()),
+            (0x0112, 3, 6u16.to_le_bytes().to_vec()),
+            (0x8769, 4, vec![]),  // Exif IFD pointer, patched below
+            (0x8825, 4, vec![]),  // GPS IFD pointer, patched below
+        ];
+        let exif_ifd: Vec<(u16, u16, Vec<u8>)> = vec![
+            (0x829a, 5, [1u32, 250].iter().flat_map(|v| v.to_le_bytes()).collect()),
+            (0x829d, 5, [28u32, 10].iter().flat_map(|v| v.to_le_bytes()).collect()),
+            (0x8827, 3, 400u16.to_le_bytes().to_vec()),
+            (0x9003, 2, b"2024:05:01 12:34:56\0".to_vec()),
+            (0x9011, 2, b"+09:00\0".to_vec()),
+        ];
+        let gps_ifd: Vec<(u16, u16, Vec<u8>)> = vec![
+            (0x0001, 2, b"N\0".to_vec()),
+            (0x0002, 5, [37u32, 1, 30, 1, 0, 1].iter().flat_map(|v| v.to_le_bytes()).collect()),
+            (0x0003, 2, b"W\0".to_vec()),
+            (0x0004, 5, [122u32, 1, 15, 1, 36, 1].iter().flat_map(|v| v.to_le_bytes()).collect()),
+        ];
+        let ifd_len = |ifd: &Vec<(u16, u16, Vec<u8>)>| 2 + ifd.len() * 12 + 4;
+        let ifd0_at = 8;
+        let exif_at = ifd0_at + ifd_len(&ifd0);
+        let gps_at = exif_at + ifd_len(&exif_ifd);
+        let data_at = gps_at + ifd_len(&gps_ifd);
+        for ifd in [&ifd0, &exif_ifd, &gps_ifd] {
+            out.extend((ifd.len() as u16).to_le_bytes());
+            for (tag, typ, value) in ifd.iter() {
+                let size = match typ { 3 => 2, 4 => 4, 5 => 8, _ => 1 };
+                let (count, value) = match tag {
+                    0x8769 => (1, (exif_at as u32).to_le_bytes().to_vec()),
+                    0x8825 => (1, (gps_at as u32).to_le_bytes().to_vec()),
+                    _ => (value.len() / size, value.clone()),
+                };
+                out.extend(tag.to_le_bytes());
+                out.extend(typ.to_le_bytes());
+                out.extend((count as u32).to_le_bytes());
+                if value.len() <= 4 {
+                    let mut inline = value.clone();
+                    inline.resize(4, 0);
+                    out.extend(inline);
+                } else {
+                    out.extend(((data_at + data.len()) as u32).to_le_bytes());
+                    data.extend(value);
+                }
+            }
+            out.extend(0u32.to_le_bytes());
+        }
+        out.extend(data);
+        out
+    }
+
+    #[test]
+    fn test_read_image_info() {
+        let base = std::env::temp_dir().join("napi-folder-test-image-info");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+
+        // a JPEG with an APP1 Exif segment spliced in after SOI
+        let jpeg_path = base.join("photo.jpg");
+        image::RgbImage::new(64, 48).save(&jpeg_path).unwrap();
+        let plain = std::fs::read(&jpeg_path).unwrap();
+        let payload: Vec<u8> = b"Exif\0\0".iter().cloned().chain(tiff_exif()).collect();
+        let mut jpeg = plain[..2].to_vec();
+        jpeg.extend([0xff, 0xe1]);
+        jpeg.extend(((payload.len() + 2) as u16).to_be_bytes());
+        jpeg.extend(payload);
+        jpeg.extend(&plain[2..]);
+        std::fs::write(&jpeg_path, &jpeg).unwrap();
+
+        let info = read_image_info(&jpeg_path).unwrap();
+        assert_eq!((info.format.as_str(), info.width, info.height), ("jpg", 64, 48));
+        assert_eq!((info.color_type.as_str(), info.bit_depth, info.frames), ("Rgb8", 8, None));
+        let exif = info.exif.unwrap();
+        assert_eq!(exif.make.as_deref(), Some("Canon"));
+        assert_eq!(exif.model.as_deref(), Some("EOS R5"));
+        assert_eq!(exif.orientation, Some(6));
+        assert_eq!(exif.exposure_time, Some(0.004));
+        assert_eq!(exif.f_number, Some(2.8));
+        assert_eq!(exif.iso, Some(400));
+        assert_eq!(exif.taken_at.as_deref(), Some("2024-05-01T12:34:56+09:00"));
+        let gps = exif.gps.unwrap();
+        assert_eq!((gps.lat, gps.alt), (37.5, None));
+        assert!((gps.lon + 122.26).abs() < 1e-9);
+        assert_eq!(read_dim(&jpeg_path), Some((64, 48, Some(String::from("2024-05-01T12:34:56+09:00")))));
+
+        // a two frame GIF, a still PNG with 16 bit gray
+        let gif_path = base.join("anim.gif");
+        {
+            let mut encoder = image::codecs::gif::GifEncoder::new(File::create(&gif_path).unwrap());
+            let frames = (0..2).map(|_| image::Frame::new(image::RgbaImage::new(10, 20)));
+            encoder.encode_frames(frames).unwrap();
+        }
+        let info = read_image_info(&gif_path).unwrap();
+        assert_eq!((info.format.as_str(), info.width, info.height, info.frames), ("gif", 10, 20, Some(2)));
+        assert!(info.exif.is_none());
+
+        let png_path = base.join("gray.png");
+        image::ImageBuffer::<image::Luma<u16>, Vec<u16>>::new(3, 5).save(&png_path).unwrap();
+        let info = read_image_info(&png_path).unwrap();
+        assert_eq!((info.color_type.as_str(), info.bit_depth, info.frames), ("L16", 16, None));
+        assert_eq!(read_dim(&png_path), Some((3, 5, None)));
+
+        std::fs::write(base.join("a.txt"), "text").unwrap();
+        assert!(read_image_info(&base.join("a.txt")).is_err());
+        assert!(read_dim(&base.join("a.txt")).is_none());
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::models::ApiError;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// `on_progress` of long jobs is called this often
+pub const PROGRESS_MS: u64 = 200;
+
+///
+/// cancel flags of the running jobs of one kind, by the absolute path they run on
+///
+/// A job started on a path that is already busy takes its place; the older one still runs
+/// but can't be canceled anymore.
+#[derive(Default)]
+pub struct Jobs(Mutex<HashMap<String, Arc<AtomicBool>>>);
+
+impl Jobs {
+    pub fn start(&self, key: &str) -> Result<Arc<AtomicBool>> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.0.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .insert(key.to_string(), cancel.clone());
+        Ok(cancel)
+    }
+
+    /// forget the job of `cancel`, unless a later one took its place
+    pub fn finish(&self, key: &str, cancel: &Arc<AtomicBool>) {
+        if let Ok(mut jobs) = self.0.lock() {
+            if jobs.get(key).is_some_and(|job| Arc::ptr_eq(job, cancel)) {
+                jobs.remove(key);
+            }
+        }
+    }
+
+    /// `false` if no job is running for `key`
+    pub fn cancel(&self, key: &str) -> Result<bool> {
+        let jobs = self.0.lock().map_err(|err| ApiError::Folder(err.to_string()))?;
+        match jobs.get(key) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    ///
+    /// run `job` on a blocking thread, registered under `key` until it returns
+    ///
+    /// `report` is called every `PROGRESS_MS` while it runs. Returns the cancel flag with the result.
+    pub async fn run<J, P, R>(&self, key: &str, report: P, job: J) -> Result<(R, Arc<AtomicBool>)>
+    where
+        J: FnOnce(&AtomicBool) -> R + Send + 'static,
+        P: Fn() + Send + 'static,
+        R: Send + 'static {
+        let cancel = self.start(key)?;
+        let done = Arc::new(AtomicBool::new(false));
+        let reporter = {
+            let done = done.clone();
+            tokio::spawn(async move {
+                loop {
+                    tokio::time::sleep(Duration::from_millis(PROGRESS_MS)).await;
+                    if done.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    report();
+                }
+            })
+        };
+        let res = {
+            let cancel = cancel.clone();
+            tokio::task::spawn_blocking(move || job(&cancel)).await
+        };
+        done.store(true, Ordering::Relaxed);
+        let _ = reporter.await;
+        self.finish(key, &cancel);
+        let res = res.map_err(|err| ApiError::Folder(err.to_string()))?;
+        Ok((res, cancel))
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_jobs() {
+        let jobs = Jobs::default();
+        assert!(!jobs.cancel("/a").unwrap());
+        let first = jobs.start("/a").unwrap();
+        let second = jobs.start("/a").unwrap();
+        // the older job is done but the newer one stays
+        jobs.finish("/a", &first);
+        assert!(jobs.cancel("/a").unwrap());
+        assert!(second.load(Ordering::Relaxed) && !first.load(Ordering::Relaxed));
+        jobs.finish("/a", &second);
+        assert!(!jobs.cancel("/a").unwrap());
+
+        let (n, cancel) = jobs.run("/b", || {}, |cancel| if cancel.load(Ordering::Relaxed) { 0 } else { 1 }).await.unwrap();
+        assert_eq!(n, 1);
+        assert!(!cancel.load(Ordering::Relaxed));
+        assert!(!jobs.cancel("/b").unwrap());
+    }
+}
@@ -3,15 +3,32 @@
 mod models;
 mod path_ext;
 mod system_time_ext;
+mod metadata_ext;
 mod api;
 mod dir;
+mod watch;
+mod filter;
+mod walk;
+mod text;
+mod search;
+mod find;
+mod name_index;
+mod text_index;
+mod hex;
+mod bytes;
+mod media;
+mod thumb;
+mod image_info;
+mod jobs;
 
 use napi_derive::napi;
-use napi::{Error as NApiError};
+use napi::Error as NApiError;
+use napi::bindgen_prelude::Buffer;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
 use serde::{Serialize, Deserialize};
 use crate::api::get_instance;
 use crate::models::{OrdItem, OrderAsc, OrderBy, MetaType, OptParams, Params,
-                    ApiError, TextContent};
+                    ApiError, TextContent, LineEnding, GrepOpt, FindMode, TextIndexOpt, ApiConfig, ThumbFormat};
 
 
 
@@ -21,28 +38,380 @@ pub struct FolderApi;
 #[napi]
 impl FolderApi {
   #[napi(constructor)]
+  #[allow(clippy::new_without_default)]
   pub fn new() -> Self {
     FolderApi
   }
 
+  ///
+  /// read a text file up to 5MB
+  ///
+  /// # arg
+  /// - path_str
+  /// - encoding: decode with this encoding instead of the detected one, e.g. one of `candidates`
+  /// - lossy: return text with U+FFFD for malformed sequences instead of no text
+  ///
+  /// returns a json `TextContent`
   #[napi]
-  pub async fn read_text(&self, path_str: String) -> Result<String, NApiError> {
-    let text_content: TextContent = get_instance().read_txt(&path_str).await.map_err(Into::<NApiError>::into)?;
+  pub async fn read_text(&self, path_str: String, encoding: Option<String>, lossy: Option<bool>) -> Result<String, NApiError> {
+    let text_content: TextContent = get_instance().read_txt(&path_str, encoding.as_deref(), lossy.unwrap_or(false)).await.map_err(Into::<NApiError>::into)?;
     self.from_obj(&text_content, false).map_err(Into::<NApiError>::into)      
   }
 
+  ///
+  /// save a text file, refusing if it changed since it was read
+  ///
+  /// # arg
+  /// - path_str
+  /// - text
+  /// - encoding: `enc` of `read_text`
+  /// - line_ending: `Lf`, `Crlf` or `Cr`, the `line_ending` of `read_text`; `text` is written as is if not given
+  /// - expected_mtime: `tm` of `read_text`, nanoseconds as a decimal string
+  ///
+  /// returns the new mtime like `tm`
+  #[napi(ts_args_type = "pathStr: string, text: string, encoding: string, lineEnding?: 'Lf' | 'Crlf' | 'Cr', expectedMtime?: string")]
+  pub async fn write_text(&self, path_str: String, text: String, encoding: String, line_ending: Option<String>, expected_mtime: Option<String>) -> Result<String, NApiError> {
+    let line_ending: Option<LineEnding> = match line_ending {
+      Some(s) => Some(serde_json::from_value(serde_json::Value::String(s)).map_err(ApiError::Json)?),
+      None => None,
+    };
+    Ok(get_instance().write_text(&path_str, &text, &encoding, line_ending, expected_mtime.as_deref()).await?)
+  }
+
+  ///
+  /// find text in a file of any size, for Ctrl+F
+  ///
+  /// # arg
+  /// - path_str
+  /// - pattern
+  /// - regex: `pattern` is a regex (default false)
+  /// - case_sensitive: default false
+  /// - max_hits: default 1000
+  ///
+  /// returns a json `SearchResult`
+  #[napi]
+  pub async fn search_in_file(&self, path_str: String, pattern: String, regex: Option<bool>, case_sensitive: Option<bool>, max_hits: Option<u32>) -> Result<String, NApiError> {
+    let res = get_instance().search_in_file(&path_str, &pattern, regex.unwrap_or(false), case_sensitive.unwrap_or(false), max_hits.unwrap_or(1000) as usize).await?;
+    self.from_obj(&res, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// search the contents of the files under a folder
+  ///
+  /// # arg
+  /// - root
+  /// - pattern
+  /// - json_opt: json `GrepOpt`
+  /// - callback: called with a json `GrepFile` for each file with matches
+  ///
+  /// returns a json `GrepSummary`, `canceled` is set if `cancel_grep` stopped it
+  #[napi(ts_args_type = "root: string, pattern: string, jsonOpt: string | undefined | null, callback: (json: string) => void")]
+  pub async fn grep(&self, root: String, pattern: String, json_opt: Option<String>, callback: ThreadsafeFunction<String, ErrorStrategy::Fatal>) -> Result<String, NApiError> {
+    let opt: GrepOpt = match json_opt {
+      Some(json_opt) => self.from_str(json_opt.as_str()).map_err(Into::<NApiError>::into)?,
+      None => GrepOpt::default(),
+    };
+    let summary = get_instance().grep(&root, &pattern, &opt, move |file| {
+      if let Ok(json) = serde_json::to_string(&file) {
+        callback.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    self.from_obj(&summary, false).map_err(Into::<NApiError>::into)
+  }
+
+  /// stop a running `grep` of `root`; returns `false` if none is running
+  #[napi]
+  pub fn cancel_grep(&self, root: String) -> Result<bool, NApiError> {
+    Ok(get_instance().cancel_grep(&root)?)
+  }
+
+  ///
+  /// find files and folders under a folder by their relative path
+  ///
+  /// # arg
+  /// - root
+  /// - query
+  /// - mode: `Glob`, `Regex`, `Fuzzy` (default) or `Substring`
+  /// - max_results: default 1000
+  /// - callback: called with a json `FindHit[]` for each folder with hits
+  ///
+  /// returns a json `FindResult`, `canceled` is set if `cancel_find` stopped it
+  #[napi(ts_args_type = "root: string, query: string, mode?: 'Glob' | 'Regex' | 'Fuzzy' | 'Substring', maxResults?: number, callback?: (json: string) => void")]
+  pub async fn find(&self, root: String, query: String, mode: Option<String>, max_results: Option<u32>, callback: Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>) -> Result<String, NApiError> {
+    let mode: FindMode = match mode {
+      Some(s) => serde_json::from_value(serde_json::Value::String(s)).map_err(ApiError::Json)?,
+      None => FindMode::default(),
+    };
+    let result = get_instance().find(&root, &query, mode, max_results.unwrap_or(1000) as usize, move |hits| {
+      let Some(callback) = &callback else {
+        return;
+      };
+      if let Ok(json) = serde_json::to_string(&hits) {
+        callback.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    self.from_obj(&result, false).map_err(Into::<NApiError>::into)
+  }
+
+  /// stop a running `find` under `root`; returns `false` if none is running
+  #[napi]
+  pub fn cancel_find(&self, root: String) -> Result<bool, NApiError> {
+    Ok(get_instance().cancel_find(&root)?)
+  }
+
+  ///
+  /// build or refresh the persisted file name index of a folder or disk
+  ///
+  /// # arg
+  /// - root: a path of `get_disks` or `get_home_dir`
+  /// - callback: called with a json `NameIndexInfo` while walking
+  ///
+  /// returns a json `NameIndexInfo`, `canceled` is set if `cancel_name_index` stopped it
+  #[napi(ts_args_type = "root: string, callback?: (json: string) => void")]
+  pub async fn update_name_index(&self, root: String, callback: Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>) -> Result<String, NApiError> {
+    let info = get_instance().update_name_index(&root, move |progress| {
+      if let (Some(tsfn), Ok(json)) = (callback.as_ref(), serde_json::to_string(&progress)) {
+        tsfn.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    self.from_obj(&info, false).map_err(Into::<NApiError>::into)
+  }
+
+  /// stop a running `update_name_index` of `root`, the previous index is kept; returns `false` if none is running
+  #[napi]
+  pub fn cancel_name_index(&self, root: String) -> Result<bool, NApiError> {
+    Ok(get_instance().cancel_name_index(&root)?)
+  }
+
+  /// json `NameIndexInfo` of the last update, or `null` if `root` was never indexed
+  #[napi]
+  pub async fn get_name_index_info(&self, root: String) -> Result<Option<String>, NApiError> {
+    match get_instance().get_name_index_info(&root).await? {
+      Some(info) => Ok(Some(self.from_obj(&info, false)?)),
+      None => Ok(None),
+    }
+  }
+
+  ///
+  /// search the file name index of a folder, like `find` but without walking it
+  ///
+  /// # arg
+  /// - root: as given to `update_name_index`
+  /// - query
+  /// - mode: `Glob`, `Regex`, `Fuzzy` or `Substring` (default)
+  /// - max_results: default 1000
+  ///
+  /// returns a json `FindResult`
+  #[napi(ts_args_type = "root: string, query: string, mode?: 'Glob' | 'Regex' | 'Fuzzy' | 'Substring', maxResults?: number")]
+  pub async fn search_name_index(&self, root: String, query: String, mode: Option<String>, max_results: Option<u32>) -> Result<String, NApiError> {
+    let mode: FindMode = match mode {
+      Some(s) => serde_json::from_value(serde_json::Value::String(s)).map_err(ApiError::Json)?,
+      None => FindMode::Substring,
+    };
+    let result = get_instance().search_name_index(&root, &query, mode, max_results.unwrap_or(1000) as usize).await?;
+    self.from_obj(&result, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// build or refresh the full-text index of a folder, opt-in per folder
+  ///
+  /// # arg
+  /// - root
+  /// - json_opt: json `TextIndexOpt`, the options of the last update if not given
+  /// - callback: called with a json `TextIndexInfo` while indexing
+  ///
+  /// returns a json `TextIndexInfo`, `canceled` is set if `cancel_text_index` stopped it
+  #[napi(ts_args_type = "root: string, jsonOpt?: string, callback?: (json: string) => void")]
+  pub async fn update_text_index(&self, root: String, json_opt: Option<String>, callback: Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>) -> Result<String, NApiError> {
+    let opt: Option<TextIndexOpt> = match json_opt {
+      Some(json_opt) => Some(self.from_str(json_opt.as_str()).map_err(Into::<NApiError>::into)?),
+      None => None,
+    };
+    let info = get_instance().update_text_index(&root, opt, move |progress| {
+      if let (Some(tsfn), Ok(json)) = (callback.as_ref(), serde_json::to_string(&progress)) {
+        tsfn.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    self.from_obj(&info, false).map_err(Into::<NApiError>::into)
+  }
+
+  /// stop a running `update_text_index` of `root`, the previous index is kept; returns `false` if none is running
+  #[napi]
+  pub fn cancel_text_index(&self, root: String) -> Result<bool, NApiError> {
+    Ok(get_instance().cancel_text_index(&root)?)
+  }
+
+  /// delete the full-text index of a folder; `false` if there was none
+  #[napi]
+  pub async fn remove_text_index(&self, root: String) -> Result<bool, NApiError> {
+    Ok(get_instance().remove_text_index(&root).await?)
+  }
+
+  /// json `TextIndexInfo` of the last update, or `null` if `root` was never indexed
+  #[napi]
+  pub async fn get_text_index_info(&self, root: String) -> Result<Option<String>, NApiError> {
+    match get_instance().get_text_index_info(&root).await? {
+      Some(info) => Ok(Some(self.from_obj(&info, false)?)),
+      None => Ok(None),
+    }
+  }
+
+  ///
+  /// search the full-text index of a folder
+  ///
+  /// # arg
+  /// - root: as given to `update_text_index`
+  /// - query: words that must all be in a document, each also matches longer words it starts
+  /// - max_results: default 100
+  /// - max_snippets: matching lines per document, default 3
+  ///
+  /// returns a json `TextIndexResult`
+  #[napi]
+  pub async fn search_text_index(&self, root: String, query: String, max_results: Option<u32>, max_snippets: Option<u32>) -> Result<String, NApiError> {
+    let result = get_instance().search_text_index(&root, &query, max_results.unwrap_or(100) as usize, max_snippets.unwrap_or(3) as usize).await?;
+    self.from_obj(&result, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// read a page of a large text file
+  ///
+  /// # arg
+  /// - path_str
+  /// - offset: byte offset, used if `line_start` is not given (`next_offset` of the previous page)
+  /// - line_start: 0 based line number
+  /// - line_cnt: max lines of the page
+  /// - max_bytes: max bytes of the page (default 1MB), a longer line is split over pages
+  ///
+  /// returns a json `TextPage`
+  #[napi]
+  pub async fn read_text_range(&self, path_str: String, offset: Option<i64>, line_start: Option<i64>, line_cnt: Option<i64>, max_bytes: Option<u32>) -> Result<String, NApiError> {
+    let to_u64 = |v: Option<i64>| v.map(|v| v.max(0) as u64);
+    let page = get_instance().read_text_range(&path_str, to_u64(offset), to_u64(line_start), to_u64(line_cnt), max_bytes.map(|v| v as usize)).await?;
+    self.from_obj(&page, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// hex dump of a part of any file
+  ///
+  /// # arg
+  /// - path_str
+  /// - offset: byte offset, moved back to its row start (`next_offset` of the previous page)
+  /// - length: bytes of the page, default 4KB, at most 256KB
+  /// - bytes_per_row: default 16, at most 64
+  ///
+  /// returns a json `HexPage`
+  #[napi]
+  pub async fn read_hex(&self, path_str: String, offset: Option<i64>, length: Option<u32>, bytes_per_row: Option<u32>) -> Result<String, NApiError> {
+    let page = get_instance().read_hex(&path_str, offset.map(|v| v.max(0) as u64), length.map(|v| v as usize), bytes_per_row.map(|v| v as usize)).await?;
+    self.from_obj(&page, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// offsets of a byte pattern in a file
+  ///
+  /// # arg
+  /// - path_str
+  /// - pattern: hex digits like `ff d8 ff` if `hex`, else text
+  /// - hex: default false
+  /// - start: byte offset to search from
+  /// - max_hits: default 1000
+  ///
+  /// returns a json `BytesResult`
+  #[napi]
+  pub async fn find_bytes(&self, path_str: String, pattern: String, hex: Option<bool>, start: Option<i64>, max_hits: Option<u32>) -> Result<String, NApiError> {
+    let result = get_instance().find_bytes(&path_str, &pattern, hex.unwrap_or(false), start.map(|v| v.max(0) as u64), max_hits.unwrap_or(1000) as usize).await?;
+    self.from_obj(&result, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// raw bytes of a file as a `Buffer`, without json
+  ///
+  /// # arg
+  /// - path_str
+  /// - offset: default 0
+  /// - length: default the rest of the file; more than `max_read_bytes` of the config is an error
+  #[napi]
+  pub async fn read_bytes(&self, path_str: String, offset: Option<i64>, length: Option<i64>) -> Result<Buffer, NApiError> {
+    let bytes = get_instance().read_bytes(&path_str, offset.map(|v| v.max(0) as u64), length.map(|v| v.max(0) as u64)).await?;
+    Ok(bytes.into())
+  }
+
+  ///
+  /// thumbnail of an image, cached as a freedesktop thumbnail
+  ///
+  /// # arg
+  /// - path_str: PNG, JPEG, GIF, BMP, WebP or TIFF
+  /// - max_size: longest side, default 256, at most 1024; smaller images are not scaled up
+  /// - format: `Png` (default) or `Jpeg`
+  #[napi(ts_args_type = "pathStr: string, maxSize?: number, format?: 'Png' | 'Jpeg'")]
+  pub async fn get_thumbnail(&self, path_str: String, max_size: Option<u32>, format: Option<String>) -> Result<Buffer, NApiError> {
+    let format: ThumbFormat = match format {
+      Some(s) => serde_json::from_value(serde_json::Value::String(s)).map_err(ApiError::Json)?,
+      None => ThumbFormat::Png,
+    };
+    let bytes = get_instance().get_thumbnail(&path_str, max_size.unwrap_or(256), format).await?;
+    Ok(bytes.into())
+  }
+
+  ///
+  /// width/height, color type, bit depth, frames and EXIF of an image, without decoding its pixels
+  ///
+  /// returns a json `ImageInfo`
+  #[napi]
+  pub async fn read_image_info(&self, path_str: String) -> Result<String, NApiError> {
+    let info = get_instance().read_image_info(&path_str).await?;
+    self.from_obj(&info, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// serve files over http on localhost so `<audio>`/`<video>` can seek with `Range`
+  ///
+  /// # arg
+  /// - roots: folders or files the returned token may read
+  ///
+  /// returns a json `MediaServerInfo`, files are at `url + encodeURIComponent(path)`
+  #[napi]
+  pub async fn start_media_server(&self, roots: Vec<String>) -> Result<String, NApiError> {
+    let info = get_instance().start_media_server(&roots).await?;
+    self.from_obj(&info, false).map_err(Into::<NApiError>::into)
+  }
+
+  /// revoke a token of `start_media_server`, the server stops with the last one
+  #[napi]
+  pub fn stop_media_server(&self, token: String) -> Result<bool, NApiError> {
+    Ok(get_instance().stop_media_server(&token)?)
+  }
+
+  /// json `ApiConfig`
+  #[napi]
+  pub fn get_config(&self) -> Result<String, NApiError> {
+    let config = get_instance().get_config()?;
+    self.from_obj(&config, false).map_err(Into::<NApiError>::into)
+  }
+
+  /// replace the config with a json `ApiConfig`, missing fields get their defaults; returns the new config
+  #[napi]
+  pub fn set_config(&self, json_config: String) -> Result<String, NApiError> {
+    let config: ApiConfig = self.from_str(json_config.as_str()).map_err(Into::<NApiError>::into)?;
+    let config = get_instance().set_config(config)?;
+    self.from_obj(&config, false).map_err(Into::<NApiError>::into)
+  }
+
   #[napi]
   pub async fn read_folder(&self, json_params: String) -> Result<String, NApiError> {
     let params: OptParams = self.from_str(json_params.as_str()).map_err(Into::<NApiError>::into)?;
 
     let new_params = Params {
       meta_types: params.meta_types.unwrap_or(vec![MetaType::Sz, MetaType::Tm]),
-      ordering: params.ordering.unwrap_or(vec![OrdItem { nm: OrderBy::Dir, asc: OrderAsc::Asc }, OrdItem { nm: OrderBy::Nm, asc: OrderAsc::Asc }]),
+      ordering: params.ordering.unwrap_or(vec![OrdItem { nm: OrderBy::Dir, asc: OrderAsc::Asc, cmp: None, locale: None }, OrdItem { nm: OrderBy::Nm, asc: OrderAsc::Asc, cmp: None, locale: None }]),
       is_pretty: params.is_pretty.unwrap_or(false),
       path_str: params.path_str.unwrap_or(String::from(".")),
       cache_nm: params.cache_nm,
       skip_n: params.skip_n,
       take_n: params.take_n,
+      depth: params.depth.unwrap_or(1),
+      max_nodes: params.max_nodes,
+      filter: params.filter,
     };
     let folder = get_instance().get_folder(&new_params).await.map_err(Into::<NApiError>::into)?;
     
@@ -93,13 +462,121 @@ impl FolderApi {
     self.from_obj(&obj, false).map_err(Into::<NApiError>::into)
   }
 
+  ///
+  /// watch a folder
+  ///
+  /// # arg
+  /// - path_str
+  /// - recursive: watch sub folders too
+  /// - callback: called with a json `WatchEvent[]` after changes settle
+  ///
+  /// returns the id for `unwatch`
+  #[napi(ts_args_type = "pathStr: string, recursive: boolean, callback: (json: string) => void")]
+  pub fn watch(&self, path_str: String, recursive: bool, callback: ThreadsafeFunction<String, ErrorStrategy::Fatal>) -> Result<u32, NApiError> {
+    let id = get_instance().watch(&path_str, recursive, move |events| {
+      if let Ok(json) = serde_json::to_string(&events) {
+        callback.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    })?;
+    Ok(id)
+  }
+
+  ///
+  /// watch mounted disks (USB sticks, network drives)
+  ///
+  /// # arg
+  /// - callback: called with a json `DiskEvent[]` when disks are added or removed
+  ///
+  /// returns the id for `unwatch`
+  #[napi(ts_args_type = "callback: (json: string) => void")]
+  pub fn watch_disks(&self, callback: ThreadsafeFunction<String, ErrorStrategy::Fatal>) -> Result<u32, NApiError> {
+    let id = get_instance().watch_disks(move |events| {
+      if let Ok(json) = serde_json::to_string(&events) {
+        callback.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    })?;
+    Ok(id)
+  }
+
+  ///
+  /// follow a log file like `tail -f`
+  ///
+  /// # arg
+  /// - path_str
+  /// - encoding: `enc` of `read_text` to decode with; detected like `read_text` does if not given
+  /// - callback: called with a json `TailEvent[]` when lines are appended or the file is truncated or rotated
+  ///
+  /// returns the id for `unwatch`
+  #[napi(ts_args_type = "pathStr: string, encoding: string | undefined | null, callback: (json: string) => void")]
+  pub async fn tail(&self, path_str: String, encoding: Option<String>, callback: ThreadsafeFunction<String, ErrorStrategy::Fatal>) -> Result<u32, NApiError> {
+    let id = get_instance().tail(&path_str, encoding.as_deref(), move |events| {
+      if let Ok(json) = serde_json::to_string(&events) {
+        callback.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    Ok(id)
+  }
+
+  /// stop a `watch`, `watch_disks` or `tail`
+  #[napi]
+  pub fn unwatch(&self, id: u32) -> Result<bool, NApiError> {
+    Ok(get_instance().unwatch(id)?)
+  }
+
+  ///
+  /// total size of a folder tree
+  ///
+  /// # arg
+  /// - path_str
+  /// - callback: called with a json `DirSize` while walking
+  ///
+  /// returns a json `DirSize`, `canceled` is set if `cancel_dir_size` stopped it
+  #[napi(ts_args_type = "pathStr: string, callback?: (json: string) => void")]
+  pub async fn compute_dir_size(&self, path_str: String, callback: Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>) -> Result<String, NApiError> {
+    let dir_size = get_instance().compute_dir_size(&path_str, move |progress| {
+      if let (Some(tsfn), Ok(json)) = (callback.as_ref(), serde_json::to_string(&progress)) {
+        tsfn.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    self.from_obj(&dir_size, false).map_err(Into::<NApiError>::into)
+  }
+
+  ///
+  /// largest files and folders under a folder, for treemap/sunburst views
+  ///
+  /// # arg
+  /// - path_str
+  /// - top_n: children kept per folder, the rest is summed in `others_sz` (default 20)
+  /// - depth: levels of children (default 3)
+  /// - callback: called with a json `DirSize` while walking
+  ///
+  /// returns a json `DiskUsage`
+  #[napi(ts_args_type = "pathStr: string, topN?: number, depth?: number, callback?: (json: string) => void")]
+  pub async fn analyze_disk_usage(&self, path_str: String, top_n: Option<u32>, depth: Option<u32>, callback: Option<ThreadsafeFunction<String, ErrorStrategy::Fatal>>) -> Result<String, NApiError> {
+    let top_n = top_n.unwrap_or(20) as usize;
+    let depth = depth.unwrap_or(3) as usize;
+    let disk_usage = get_instance().analyze_disk_usage(&path_str, top_n, depth, move |progress| {
+      if let (Some(tsfn), Ok(json)) = (callback.as_ref(), serde_json::to_string(&progress)) {
+        tsfn.call(json, ThreadsafeFunctionCallMode::NonBlocking);
+      }
+    }).await?;
+    self.from_obj(&disk_usage, false).map_err(Into::<NApiError>::into)
+  }
+
+  #[napi]
+  pub fn cancel_dir_size(&self, path_str: String) -> Result<bool, NApiError> {
+    Ok(get_instance().cancel_dir_size(&path_str)?)
+  }
+
+  #[allow(clippy::wrong_self_convention)]
   fn from_str<'a, T> (&self, json_str: &'a str) -> Result<T, ApiError>
   where
       T: Deserialize<'a> {
     Ok(serde_json::from_str(json_str)?)
   }
 
-  fn from_obj<'a, T> (&self, obj: &T, is_pretty: bool) -> Result<String, ApiError>
+  #[allow(clippy::wrong_self_convention)]
+  fn from_obj<T> (&self, obj: &T, is_pretty: bool) -> Result<String, ApiError>
     where T: ?Sized + Serialize, {
     
     let fn_json = if is_pretty {
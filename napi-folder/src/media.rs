@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+use mime_guess::from_path;
+use crate::models::ApiError;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// request line and headers larger than this are refused
+const MAX_HEAD_SZ: usize = 16 * 1024;
+/// connections that don't send a complete request head within this are closed
+const HEAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// allowed roots of each token, canonicalized
+type Tokens = Arc<Mutex<HashMap<String, Vec<PathBuf>>>>;
+
+///
+/// localhost HTTP server for `<audio>`/`<video>`, with byte ranges for seeking
+///
+/// `GET /<token>/<percent-encoded path>` serves a file if it is under one of the roots of the token.
+/// `HEAD`, single `Range: bytes=` ranges and keep-alive are supported.
+pub struct MediaServer {
+    pub port: u16,
+    tokens: Tokens,
+    task: JoinHandle<()>,
+}
+
+impl MediaServer {
+    /// listen on a free port of 127.0.0.1
+    pub async fn start() -> Result<MediaServer> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let port = listener.local_addr()?.port();
+        let tokens: Tokens = Arc::new(Mutex::new(HashMap::new()));
+        let task = {
+            let tokens = tokens.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let tokens = tokens.clone();
+                    tokio::spawn(async move {
+                        let _ = serve_conn(stream, tokens).await;
+                    });
+                }
+            })
+        };
+        Ok(MediaServer { port, tokens, task })
+    }
+
+    /// a new token allowing the files under `roots`; roots that don't exist are left out
+    pub fn add_token(&self, roots: &[String]) -> Result<String> {
+        let roots: Vec<PathBuf> = roots.iter()
+            .filter_map(|root| std::fs::canonicalize(root).ok())
+            .collect();
+        if roots.is_empty() {
+            return Err(ApiError::Folder(String::from("Err MediaServer: no existing root")));
+        }
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        self.tokens.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .insert(token.clone(), roots);
+        Ok(token)
+    }
+
+    /// returns `false` if `token` was unknown
+    pub fn remove_token(&self, token: &str) -> Result<bool> {
+        Ok(self.tokens.lock()
+            .map_err(|err| ApiError::Folder(err.to_string()))?
+            .remove(token)
+            .is_some())
+    }
+
+    pub fn token_cnt(&self) -> usize {
+        self.tokens.lock().map(|tokens| tokens.len()).unwrap_or_default()
+    }
+
+    /// stop accepting connections, open ones end with their current request
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+/// requests of one connection, until it is closed or asks for `Connection: close`
+async fn serve_conn(stream: TcpStream, tokens: Tokens) -> std::io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let Ok(head) = tokio::time::timeout(HEAD_TIMEOUT, read_head(&mut reader)).await else {
+            return Ok(());
+        };
+        let Some(head) = head? else {
+            return Ok(());
+        };
+        let keep_alive = respond(reader.get_mut(), &head, &tokens).await?;
+        if !keep_alive {
+            return reader.get_mut().shutdown().await;
+        }
+    }
+}
+
+struct Head {
+    method: String,
+    target: String,
+    http10: bool,
+    headers: HashMap<String, String>,
+}
+
+impl Head {
+    fn header(&self, nm: &str) -> Option<&str> {
+        self.headers.get(nm).map(|val| val.as_str())
+    }
+}
+
+/// request line and headers, `None` at the end of the connection
+async fn read_head<R: AsyncBufReadExt + Unpin>(reader: &mut R) -> std::io::Result<Option<Head>> {
+    let mut lines: Vec<String> = vec![];
+    let mut total = 0;
+    loop {
+        let mut line = String::new();
+        // bounded so a line without `\n` can't grow without limit
+        let n = (&mut *reader).take((MAX_HEAD_SZ - total) as u64).read_line(&mut line).await?;
+        if n == 0 {
+            if total == MAX_HEAD_SZ {
+                return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "request head too large"));
+            }
+            return Ok(None);
+        }
+        total += n;
+        if !line.ends_with('\n') && total == MAX_HEAD_SZ {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "request head too large"));
+        }
+        let line = line.trim_end();
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        } else if !lines.is_empty() {
+            break;
+        }
+    }
+    let mut parts = lines[0].split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "bad request line"));
+    };
+    let headers = lines[1..].iter()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(nm, val)| (nm.trim().to_lowercase(), val.trim().to_string()))
+        .collect();
+    Ok(Some(Head {
+        method: method.to_string(),
+        target: target.to_string(),
+        http10: version == "HTTP/1.0",
+        headers,
+    }))
+}
+
+/// answer one request; returns whether the connection stays open
+async fn respond<W: AsyncWrite + Unpin>(stream: &mut W, head: &Head, tokens: &Tokens) -> std::io::Result<bool> {
+    let keep_alive = !head.http10 && !head.header("connection").is_some_and(|val| val.eq_ignore_ascii_case("close"));
+    if head.method != "GET" && head.method != "HEAD" {
+        write_status(stream, "405 Method Not Allowed", &[("Allow", "GET, HEAD")]).await?;
+        return Ok(keep_alive);
+    }
+    let path = match resolve(&head.target, tokens).await {
+        Ok(path) => path,
+        Err(status) => {
+            write_status(stream, status, &[]).await?;
+            return Ok(keep_alive);
+        }
+    };
+    let mut file = match tokio::fs::File::open(&path).await {
+        Ok(file) => file,
+        Err(_) => {
+            write_status(stream, "404 Not Found", &[]).await?;
+            return Ok(keep_alive);
+        }
+    };
+    let sz = file.metadata().await?.len();
+    let (status, start, len) = match head.header("range").and_then(|range| parse_range(range, sz)) {
+        None => ("200 OK", 0, sz),
+        Some(Some((start, end))) => ("206 Partial Content", start, end - start + 1),
+        Some(None) => {
+            write_status(stream, "416 Range Not Satisfiable", &[("Content-Range", &format!("bytes */{}", sz))]).await?;
+            return Ok(keep_alive);
+        }
+    };
+
+    let mut resp = format!(
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nAccept-Ranges: bytes\r\nCache-Control: no-cache\r\n",
+        status, content_type(&path), len);
+    if status.starts_with("206") {
+        resp.push_str(&format!("Content-Range: bytes {}-{}/{}\r\n", start, start + len - 1, sz));
+    }
+    resp.push_str(if keep_alive { "Connection: keep-alive\r\n\r\n" } else { "Connection: close\r\n\r\n" });
+    stream.write_all(resp.as_bytes()).await?;
+    if head.method == "GET" {
+        file.seek(SeekFrom::Start(start)).await?;
+        let sent = tokio::io::copy(&mut file.take(len), stream).await?;
+        if sent < len {
+            // the file shrank, the promised length can't be kept
+            return Ok(false);
+        }
+    }
+    stream.flush().await?;
+    Ok(keep_alive)
+}
+
+async fn write_status<W: AsyncWrite + Unpin>(stream: &mut W, status: &str, headers: &[(&str, &str)]) -> std::io::Result<()> {
+    let mut resp = format!("HTTP/1.1 {}\r\nContent-Length: 0\r\n", status);
+    for (nm, val) in headers {
+        resp.push_str(&format!("{}: {}\r\n", nm, val));
+    }
+    resp.push_str("\r\n");
+    stream.write_all(resp.as_bytes()).await?;
+    stream.flush().await
+}
+
+/// the file of `/<token>/<percent-encoded path>` if the token allows it, else the status to answer with
+async fn resolve(target: &str, tokens: &Tokens) -> std::result::Result<PathBuf, &'static str> {
+    let target = target.split_once('?').map(|(path, _)| path).unwrap_or(target);
+    let (token, encoded) = target.trim_start_matches('/').split_once('/').ok_or("404 Not Found")?;
+    let roots = tokens.lock().ok()
+        .and_then(|tokens| tokens.get(token).cloned())
+        .ok_or("403 Forbidden")?;
+    let path = percent_decode(encoded).ok_or("400 Bad Request")?;
+    let path = tokio::fs::canonicalize(&path).await.map_err(|_| "404 Not Found")?;
+    if !roots.iter().any(|root| path.starts_with(root)) {
+        return Err("403 Forbidden");
+    }
+    if !tokio::fs::metadata(&path).await.is_ok_and(|meta| meta.is_file()) {
+        return Err("404 Not Found");
+    }
+    Ok(path)
+}
+
+/// `%XX` escapes to bytes, `None` if malformed or not UTF-8
+fn percent_decode(s: &str) -> Option<String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(i + 1..i + 3)?).ok()?;
+            out.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+///
+/// first and last byte of a `Range` header
+///
+/// `None` if the header is to be ignored (not `bytes`, several ranges, malformed), `Some(None)` if it
+/// can't be satisfied.
+fn parse_range(value: &str, sz: u64) -> Option<Option<(u64, u64)>> {
+    let spec = value.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (first, last) = spec.split_once('-')?;
+    let (first, last) = (first.trim(), last.trim());
+    if first.is_empty() {
+        let suffix: u64 = last.parse().ok()?;
+        if suffix == 0 || sz == 0 {
+            return Some(None);
+        }
+        return Some(Some((sz.saturating_sub(suffix), sz - 1)));
+    }
+    let start: u64 = first.parse().ok()?;
+    let end: u64 = if last.is_empty() { u64::MAX } else { last.parse().ok()? };
+    if start >= sz || start > end {
+        return Some(None);
+    }
+    Some(Some((start, end.min(sz - 1))))
+}
+
+/// by extension, else by the file's signature
+fn content_type(path: &Path) -> String {
+    match from_path(path).first() {
+        Some(mime) => mime.essence_str().to_string(),
+        None => infer::get_from_path(path).ok().flatten()
+            .map(|kind| kind.mime_type().to_string())
+            .unwrap_or_else(|| String::from("application/octet-stream")),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(Some((0, 99))));
+        assert_eq!(parse_range("bytes=900-", 1000), Some(Some((900, 999))));
+        assert_eq!(parse_range("bytes=900-5000", 1000), Some(Some((900, 999))));
+        assert_eq!(parse_range("bytes=-100", 1000), Some(Some((900, 999))));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some(Some((0, 999))));
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(None));
+        assert_eq!(parse_range("bytes=50-10", 1000), Some(None));
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+        assert_eq!(parse_range("bytes=a-", 1000), None);
+    }
+
+    #[tokio::test]
+    async fn test_read_head() {
+        let req = b"GET /t/a.mp3 HTTP/1.1\r\nRange: bytes=0-\r\n\r\n";
+        let head = read_head(&mut &req[..]).await.unwrap().unwrap();
+        assert_eq!((head.method.as_str(), head.target.as_str()), ("GET", "/t/a.mp3"));
+        assert_eq!(head.header("range"), Some("bytes=0-"));
+        assert!(read_head(&mut &b""[..]).await.unwrap().is_none());
+
+        // a line without `\n` is refused at the limit instead of buffered
+        let long = vec![b'a'; MAX_HEAD_SZ * 4];
+        assert!(read_head(&mut &long[..]).await.is_err());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("%2Fhome%2F%EC%9D%8C%EC%95%85%20a.mp3").unwrap(), "/home/음악 a.mp3");
+        assert_eq!(percent_decode("C%3A%5Cmusic").unwrap(), r"C:\music");
+        assert!(percent_decode("%zz").is_none());
+        assert!(percent_decode("%2").is_none());
+        assert!(percent_decode("%ff").is_none());
+    }
+}
@@ -0,0 +1,170 @@
+use std::fs::Metadata;
+#[cfg(unix)]
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::ffi::CStr;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+#[cfg(unix)]
+use std::sync::{Mutex, OnceLock};
+
+pub trait MetaExt {
+    /// `ls -l` style, e.g. `drwxr-xr-x`
+    fn to_mode_str(&self) -> String;
+    /// e.g. `0755`
+    fn to_mode_oct(&self) -> String;
+    fn owner_nm(&self) -> Option<String>;
+    fn group_nm(&self) -> Option<String>;
+    fn inode(&self) -> Option<u64>;
+    fn nlink(&self) -> Option<u64>;
+}
+
+#[cfg(unix)]
+impl MetaExt for Metadata {
+    fn to_mode_str(&self) -> String {
+        let mode = self.mode();
+        let file_type = self.file_type();
+        let mut s = String::with_capacity(10);
+        s.push(if file_type.is_dir() {
+            'd'
+        } else if file_type.is_symlink() {
+            'l'
+        } else if file_type.is_file() {
+            '-'
+        } else {
+            '?'
+        });
+        s.push_str(&rwx_str(mode));
+        s
+    }
+
+    fn to_mode_oct(&self) -> String {
+        format!("{:04o}", self.mode() & 0o7777)
+    }
+
+    fn owner_nm(&self) -> Option<String> {
+        Some(user_nm(self.uid()))
+    }
+
+    fn group_nm(&self) -> Option<String> {
+        Some(group_nm(self.gid()))
+    }
+
+    fn inode(&self) -> Option<u64> {
+        Some(self.ino())
+    }
+
+    fn nlink(&self) -> Option<u64> {
+        Some(MetadataExt::nlink(self))
+    }
+}
+
+/// Windows has no mode bits, the read-only attribute is mapped like `std` does
+#[cfg(not(unix))]
+impl MetaExt for Metadata {
+    fn to_mode_str(&self) -> String {
+        let dir = if self.is_dir() { 'd' } else { '-' };
+        format!("{}{}", dir, rwx_str(readonly_mode(self.permissions().readonly())))
+    }
+
+    fn to_mode_oct(&self) -> String {
+        format!("{:04o}", readonly_mode(self.permissions().readonly()))
+    }
+
+    fn owner_nm(&self) -> Option<String> {
+        None
+    }
+
+    fn group_nm(&self) -> Option<String> {
+        None
+    }
+
+    fn inode(&self) -> Option<u64> {
+        None
+    }
+
+    fn nlink(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg_attr(unix, allow(dead_code))]
+pub fn readonly_mode(readonly: bool) -> u32 {
+    if readonly { 0o444 } else { 0o666 }
+}
+
+/// permission bits to `rwxr-xr-x`, including setuid/setgid/sticky
+pub fn rwx_str(mode: u32) -> String {
+    let mut s = String::with_capacity(9);
+    for (shift, special, special_ch) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits = (mode >> shift) & 0o7;
+        s.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        s.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        let x = bits & 0o1 != 0;
+        s.push(match (mode & special != 0, x) {
+            (true, true) => special_ch,
+            (true, false) => special_ch.to_ascii_uppercase(),
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    s
+}
+
+/// user name of `uid`, the number if it has no passwd entry
+#[cfg(unix)]
+pub fn user_nm(uid: u32) -> String {
+    static NAMES: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+    cached_nm(&NAMES, uid, |buf| {
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::passwd = std::ptr::null_mut();
+        let rc = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+        let nm = (rc == 0 && !result.is_null())
+            .then(|| unsafe { CStr::from_ptr(pwd.pw_name) }.to_string_lossy().into_owned());
+        (rc, nm)
+    })
+}
+
+/// group name of `gid`, the number if it has no group entry
+#[cfg(unix)]
+pub fn group_nm(gid: u32) -> String {
+    static NAMES: OnceLock<Mutex<HashMap<u32, String>>> = OnceLock::new();
+    cached_nm(&NAMES, gid, |buf| {
+        let mut grp: libc::group = unsafe { std::mem::zeroed() };
+        let mut result: *mut libc::group = std::ptr::null_mut();
+        let rc = unsafe { libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+        let nm = (rc == 0 && !result.is_null())
+            .then(|| unsafe { CStr::from_ptr(grp.gr_name) }.to_string_lossy().into_owned());
+        (rc, nm)
+    })
+}
+
+/// larger buffers are not tried, an entry that big is taken as missing
+#[cfg(unix)]
+const MAX_ENTRY_BUF: usize = 1024 * 1024;
+
+///
+/// name of `id` from `names`, else from `lookup` and remembered
+///
+/// `lookup` is a `get*_r` call returning its error code and the name; the buffer is doubled while
+/// it reports `ERANGE`. The number if there is no entry.
+#[cfg(unix)]
+fn cached_nm<F>(names: &OnceLock<Mutex<HashMap<u32, String>>>, id: u32, lookup: F) -> String
+where
+    F: Fn(&mut [libc::c_char]) -> (libc::c_int, Option<String>) {
+    let names = names.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(nm) = names.lock().ok().and_then(|m| m.get(&id).cloned()) {
+        return nm;
+    }
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let nm = loop {
+        match lookup(&mut buf) {
+            (libc::ERANGE, _) if buf.len() < MAX_ENTRY_BUF => buf.resize(buf.len() * 2, 0),
+            (_, nm) => break nm.unwrap_or_else(|| id.to_string()),
+        }
+    };
+    if let Ok(mut m) = names.lock() {
+        m.insert(id, nm.clone());
+    }
+    nm
+}
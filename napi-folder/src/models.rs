@@ -1,12 +1,10 @@
 use std::collections::BTreeSet;
 use std::time::SystemTime;
-use napi;
 use serde::{Serialize, Deserialize};
 use serde_with::{serde_as, skip_serializing_none};
 use ts_rs::TS;
 use thiserror::Error;
 use std::io;
-use serde_json;
 use napi::{Error as NApiError, Status};
 
 #[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, PartialOrd, Ord, Debug)]
@@ -16,6 +14,21 @@ pub enum MetaType {
     Tm,
     Mt,
     Ext,
+    Ctime,
+    Atime,
+    Mode,
+    Owner,
+    Group,
+    Hidden,
+    Readonly,
+    Symlink,
+    Inode,
+    Nlink,
+    ChildCnt,
+    HasChildren,
+    DirSz,
+    /// `width`/`height`/`taken_at` of images, read from their headers
+    Dim,
 }
 
 #[allow(dead_code)]
@@ -28,6 +41,20 @@ pub enum OrderBy {
     Tm,
     Mt,
     Ext,
+    Ctime,
+    Atime,
+    Mode,
+    Owner,
+    Group,
+    Hidden,
+    Readonly,
+    Symlink,
+    Inode,
+    Nlink,
+    ChildCnt,
+    /// pixels, `width * height`
+    Dim,
+    TakenAt,
 }
 
 #[allow(dead_code)]
@@ -38,7 +65,23 @@ pub enum OrderAsc {
     Desc,
 }
 
+/// how `Nm`, `Ext` and `Mt` are compared
+#[derive(TS, Serialize, Deserialize, Eq, Clone, PartialEq, Hash, Debug, Default)]
+#[ts(export)]
+pub enum OrderCmp {
+    /// lowercased, byte-wise
+    #[default]
+    Lower,
+    /// lowercased, digit runs compared as numbers: `img2` < `img10`
+    Natural,
+    /// Unicode collation for `OrdItem.locale` (root collation if none), digits as numbers
+    Locale,
+    /// byte-wise, case-sensitive
+    Case,
+}
+
 #[allow(dead_code)]
+#[allow(clippy::enum_variant_names)]
 #[derive(TS, Serialize, Deserialize, Eq, Clone, PartialEq, Hash, Debug)]
 #[ts(export)]
 pub enum HomeType {
@@ -62,30 +105,112 @@ pub enum HomeType {
 }
 
 
+#[skip_serializing_none]
 #[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
 #[ts(export)]
 pub struct OrdItem {
     pub nm: OrderBy,
     pub asc: OrderAsc,
+    #[ts(optional)]
+    pub cmp: Option<OrderCmp>,
+    /// BCP 47 tag for `OrderCmp::Locale`, e.g. `ko`, `de`
+    #[ts(optional)]
+    pub locale: Option<String>,
 }
 
-#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug, Default)]
+#[ts(export)]
+pub enum DiskKind {
+    Ssd,
+    Hdd,
+    #[default]
+    Unknown,
+}
+
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug, Default)]
 #[ts(export)]
 pub struct DiskInfo {
     pub path: String,
+    pub nm: String,  // volume label or device name
+    pub display_nm: String,  // `nm (path)`, or `path` if there is no name
+    pub fs: String,
+    pub kind: DiskKind,
+    pub total: u64,
+    pub available: u64,
+    pub removable: bool,
+    pub read_only: bool,
+}
+
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[ts(export)]
+pub enum DiskEventKind {
+    Added,
+    Removed,
 }
 
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[ts(export)]
+pub struct DiskEvent {
+    pub kind: DiskEventKind,
+    pub disk: DiskInfo,
+}
 
 
+
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[ts(export)]
+pub enum WatchKind {
+    Created,
+    Removed,
+    Renamed,
+    Modified,
+}
+
+#[skip_serializing_none]
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[ts(export, optional_fields)]
+pub struct WatchEvent {
+    pub kind: WatchKind,
+    pub path: String,
+    pub from: Option<String>,
+}
+
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[ts(export)]
+pub enum TailKind {
+    Lines,
+    Truncated,
+    Rotated,
+}
+
+/// event of `tail`, `lines` is set for `Lines`
+#[skip_serializing_none]
+#[derive(TS, Serialize, Deserialize, Clone, Eq, PartialEq, Hash, Debug)]
+#[ts(export, optional_fields)]
+pub struct TailEvent {
+    pub kind: TailKind,
+    pub lines: Option<Vec<String>>,
+}
+
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct CacheKey {
     pub nm: String,
     pub path: String,
     pub tm: SystemTime,
     pub meta_types: BTreeSet<MetaType>,
+    pub filter: Option<String>,
+    /// the listing of a filtered tree keeps the folders the filter drops
+    pub keep_dirs: bool,
 }
 
 
+#[derive(Clone, Eq, PartialEq, Hash)]
+pub struct CacheDirSzKey {
+    pub path: String,
+    pub tm: SystemTime,
+}
+
+#[allow(dead_code)]
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct CachePathsKey {
     pub nm: String,
@@ -94,6 +219,7 @@ pub struct CachePathsKey {
 }
 
 
+#[allow(dead_code)]
 #[derive(Clone, Eq, PartialEq, Hash)]
 pub struct CacheFileKey {
     pub nm: String,
@@ -136,9 +262,380 @@ pub struct Item {
     pub mt: Option<String>,
     pub sz: Option<u64>,  // u64
     pub tm: Option<u64>,  // u64
+    pub ctime: Option<u64>,  // creation
+    pub atime: Option<u64>,
+    pub mode: Option<String>,  // drwxr-xr-x
+    pub mode_oct: Option<String>,  // 0755
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub hidden: Option<bool>,
+    pub readonly: Option<bool>,
+    pub symlink: Option<String>,  // link target
+    pub broken: Option<bool>,  // link target missing
+    pub inode: Option<u64>,
+    pub nlink: Option<u64>,
+    pub child_cnt: Option<usize>,
+    pub has_children: Option<bool>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub taken_at: Option<String>,  // EXIF, 2024-05-01T12:34:56
     pub items: Option<Vec<Item>>
 }
 
+/// total of a folder tree; also the progress of `compute_dir_size`
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct DirSize {
+    pub path: String,
+    pub sz: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub canceled: Option<bool>,
+}
+
+/// node of `analyze_disk_usage`, a file or a folder with its largest children
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct UsageNode {
+    pub nm: String,
+    pub dir: bool,
+    pub sz: u64,
+    pub files: u64,
+    pub dirs: u64,
+    pub pct: f64,  // of the root
+    pub pct_parent: f64,
+    pub others_sz: Option<u64>,  // children not in `children`
+    pub others_cnt: Option<u64>,
+    pub children: Option<Vec<UsageNode>>,
+}
+
+/// match of `search_in_file`/`grep`
+#[derive(TS, Serialize, Clone, Debug, Default, PartialEq)]
+#[ts(export)]
+pub struct SearchHit {
+    /// 0 based
+    pub line_no: u64,
+    /// byte offset in the file, for `read_text_range`
+    pub offset: u64,
+    /// char offset in the line
+    pub col: usize,
+    /// chars of the match
+    pub len: usize,
+    /// the line around the match
+    pub snippet: String,
+    /// char range of the match in `snippet`
+    pub hl: (usize, usize),
+}
+
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct SearchResult {
+    pub path: String,
+    pub enc: String,
+    pub hits: Vec<SearchHit>,
+    /// stopped at `max_hits`
+    pub truncated: bool,
+}
+
+/// options of `grep`
+#[skip_serializing_none]
+#[derive(TS, Serialize, Deserialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct GrepOpt {
+    /// the pattern is a regex, else a literal
+    pub regex: Option<bool>,
+    pub case_sensitive: Option<bool>,
+    /// file name globs like `*.rs`, or relative path globs like `src/*.rs`
+    pub include: Option<Vec<String>>,
+    /// globs of files and folders to skip, e.g. `node_modules`
+    pub exclude: Option<Vec<String>>,
+    /// search hidden files and folders
+    pub hidden: Option<bool>,
+    /// default 10000
+    pub max_hits: Option<u64>,
+    /// default 100
+    pub max_file_hits: Option<usize>,
+}
+
+/// matches of `grep` in one file, sent while the search goes on
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct GrepFile {
+    pub path: String,
+    /// relative to the root, `/` separated
+    pub rel: String,
+    pub enc: String,
+    pub hits: Vec<SearchHit>,
+    pub truncated: bool,
+}
+
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct GrepSummary {
+    pub root: String,
+    /// files searched
+    pub files: u64,
+    pub matched_files: u64,
+    pub hits: u64,
+    /// files skipped as binary
+    pub binaries: u64,
+    /// reached `max_hits`
+    pub truncated: bool,
+    pub canceled: Option<bool>,
+}
+
+#[derive(TS, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+#[ts(export)]
+pub enum FindMode {
+    /// `*` and `?` on the name, or on the relative path if the query has a `/`
+    Glob,
+    /// on the relative path, case-insensitive
+    Regex,
+    /// fzf-style on the relative path, case-sensitive only if the query has upper case
+    #[default]
+    Fuzzy,
+    /// on the relative path, case-insensitive
+    Substring,
+}
+
+/// file or folder found by `find`
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct FindHit {
+    /// relative to the root, `/` separated
+    pub rel: String,
+    /// higher is better, 0 for glob and regex
+    pub score: i64,
+    /// ranges of `rel` that matched, in UTF-16 code units like JS string indices
+    pub hl: Vec<(usize, usize)>,
+    pub item: Item,
+}
+
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct FindResult {
+    pub root: String,
+    /// the best `max_results` hits
+    pub hits: Vec<FindHit>,
+    /// hits found in total
+    pub total: usize,
+    pub canceled: Option<bool>,
+}
+
+/// persisted file name index of a root; also the progress of `update_name_index`
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct NameIndexInfo {
+    pub root: String,
+    pub files: u64,
+    pub dirs: u64,
+    /// folders read by the update, the others were unchanged
+    pub read_dirs: Option<u64>,
+    /// seconds, when the index was updated, 0 while updating
+    pub tm: u64,
+    pub canceled: Option<bool>,
+}
+
+/// what `update_text_index` indexes
+#[skip_serializing_none]
+#[derive(TS, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[ts(export, optional_fields)]
+pub struct TextIndexOpt {
+    /// globs of the files to index, e.g. `*.md`; all text files if empty
+    pub include: Option<Vec<String>>,
+    /// globs of files and folders to skip
+    pub exclude: Option<Vec<String>>,
+    /// index hidden files and folders
+    pub hidden: Option<bool>,
+    /// bytes, larger files are skipped; 4MB if not set
+    pub max_file_sz: Option<u64>,
+}
+
+/// persisted full-text index of a root; also the progress of `update_text_index`
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct TextIndexInfo {
+    pub root: String,
+    pub docs: u64,
+    /// distinct words
+    pub terms: u64,
+    /// files read by the update, the others were unchanged
+    pub read_docs: Option<u64>,
+    /// seconds, when the index was updated, 0 while updating
+    pub tm: u64,
+    pub canceled: Option<bool>,
+}
+
+/// document of `search_text_index`
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct TextIndexHit {
+    pub path: String,
+    pub rel: String,
+    /// BM25, higher is better
+    pub score: f64,
+    /// the first lines with query words, empty if the file changed since it was indexed
+    pub hits: Vec<SearchHit>,
+}
+
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct TextIndexResult {
+    pub root: String,
+    pub hits: Vec<TextIndexHit>,
+    /// matching documents in total
+    pub total: usize,
+}
+
+/// row of `read_hex`
+#[derive(TS, Serialize, Clone, Debug, Default, PartialEq)]
+#[ts(export)]
+pub struct HexRow {
+    pub offset: u64,
+    /// lowercase, space separated: `4d 5a 90 00`
+    pub hex: String,
+    /// printable ASCII, `.` for other bytes
+    pub ascii: String,
+}
+
+/// page of `read_hex`
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct HexPage {
+    pub path: String,
+    pub sz: u64,
+    /// start of the first row
+    pub offset: u64,
+    /// `offset` of the next page
+    pub next_offset: u64,
+    pub bytes_per_row: usize,
+    pub rows: Vec<HexRow>,
+    pub eof: bool,
+}
+
+/// result of `find_bytes`
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct BytesResult {
+    pub path: String,
+    /// bytes of the pattern, the length of each match
+    pub len: usize,
+    pub offsets: Vec<u64>,
+    /// stopped at `max_hits`
+    pub truncated: bool,
+}
+
+/// position of a photo in degrees, south and west negative
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default, PartialEq)]
+#[ts(export, optional_fields)]
+pub struct GpsPos {
+    pub lat: f64,
+    pub lon: f64,
+    /// meters above sea level
+    pub alt: Option<f64>,
+}
+
+/// EXIF tags of `read_image_info`, tags the file doesn't have are left out
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default, PartialEq)]
+#[ts(export, optional_fields)]
+pub struct ExifInfo {
+    pub make: Option<String>,
+    pub model: Option<String>,
+    pub lens: Option<String>,
+    /// seconds
+    pub exposure_time: Option<f64>,
+    pub f_number: Option<f64>,
+    pub iso: Option<u32>,
+    /// mm
+    pub focal_length: Option<f64>,
+    /// `2024-05-01T12:34:56`, with `+09:00` if the camera recorded its offset
+    pub taken_at: Option<String>,
+    /// 1 to 8, 5 to 8 swap width and height for display
+    pub orientation: Option<u16>,
+    pub gps: Option<GpsPos>,
+}
+
+/// returned by `read_image_info`, from the headers only
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct ImageInfo {
+    pub path: String,
+    /// usual extension of the format: `png`, `jpg`, `gif`, `bmp`, `webp` or `tiff`
+    pub format: String,
+    pub width: u32,
+    pub height: u32,
+    /// `Rgb8`, `Rgba16`, `L8`...
+    pub color_type: String,
+    /// bits per channel
+    pub bit_depth: u8,
+    /// frames of animations, not set for still images
+    pub frames: Option<u32>,
+    pub exif: Option<ExifInfo>,
+}
+
+/// encoding of `get_thumbnail`
+#[derive(TS, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+#[ts(export)]
+pub enum ThumbFormat {
+    /// keeps transparency
+    #[default]
+    Png,
+    /// smaller, for photos
+    Jpeg,
+}
+
+/// settings of the api, replaced with `set_config`; fields left out get their defaults
+#[derive(TS, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[ts(export)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// most bytes `read_bytes` returns at once
+    pub max_read_bytes: u64,
+    /// `read_bytes` of at least this many bytes map the file instead of reading it (unix), 0 never maps
+    ///
+    /// Off by default: a file truncated by another process while it is copied kills the process.
+    pub mmap_min_bytes: u64,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        ApiConfig {
+            max_read_bytes: 64 * 1024 * 1024,
+            mmap_min_bytes: 0,
+        }
+    }
+}
+
+/// returned by `start_media_server`
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct MediaServerInfo {
+    pub port: u16,
+    pub token: String,
+    /// append the percent-encoded path of a file: `url + encodeURIComponent(path)`
+    pub url: String,
+}
+
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export, optional_fields)]
+pub struct DiskUsage {
+    pub path: String,
+    pub top_n: usize,
+    pub depth: usize,
+    pub root: Option<UsageNode>,
+    pub canceled: Option<bool>,
+}
+
 #[allow(dead_code)]
 #[skip_serializing_none]
 #[serde_as]
@@ -149,6 +646,97 @@ pub struct TextContent {
     pub mimetype: String,
     pub enc: Option<String>,
     pub text: Option<String>,
+    /// the file starts with a byte order mark, which also decided `enc`
+    pub bom: Option<bool>,
+    /// `false` if the detected `enc` is likely to be wrong, not set if the encoding was given
+    pub confident: Option<bool>,
+    /// malformed sequences, replaced by U+FFFD in lossy mode
+    pub err_cnt: Option<usize>,
+    /// from the first `DETECT_SZ` bytes, not set if the encoding was given
+    pub candidates: Option<Vec<EncCandidate>>,
+    /// the most used line ending
+    pub line_ending: Option<LineEnding>,
+    /// mtime in nanoseconds as a decimal string (too big for a JS number), the `expected_mtime` of `write_text`
+    pub tm: Option<String>,
+    /// set with `text`
+    pub stats: Option<TextStats>,
+}
+
+/// status bar numbers of a text, lines end at LF or CRLF, or at CR in a text without LF
+#[skip_serializing_none]
+#[derive(TS, Serialize, Clone, Debug, Default, PartialEq)]
+#[ts(export, optional_fields)]
+pub struct TextStats {
+    pub line_cnt: usize,
+    /// runs of non-whitespace
+    pub word_cnt: usize,
+    pub char_cnt: usize,
+    /// more than one kind of line ending, the most used one is `TextContent.line_ending`
+    pub mixed_ending: bool,
+    pub indent: Option<Indent>,
+    /// non-blank lines indented with a tab
+    pub tab_lines: usize,
+    /// non-blank lines indented with a space
+    pub space_lines: usize,
+    pub trailing_ws_lines: usize,
+    /// chars of the longest line
+    pub longest_line: usize,
+    /// 0 based
+    pub longest_line_no: usize,
+    pub ends_with_nl: bool,
+}
+
+#[derive(TS, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug)]
+#[ts(export)]
+pub enum Indent {
+    Tabs,
+    Spaces,
+    Mixed,
+}
+
+#[derive(TS, Serialize, Deserialize, Clone, Copy, Eq, PartialEq, Hash, Debug, Default)]
+#[ts(export)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    Crlf,
+    Cr,
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// encoding offered to reopen a text file with
+#[derive(TS, Serialize, Clone, Debug, Default, PartialEq)]
+#[ts(export)]
+pub struct EncCandidate {
+    pub enc: String,
+    pub err_cnt: usize,
+}
+
+/// page of `read_text_range`, `next_offset`/`next_line` continue with the following page
+#[derive(TS, Serialize, Clone, Debug, Default)]
+#[ts(export)]
+pub struct TextPage {
+    pub path: String,
+    pub enc: String,
+    pub sz: u64,
+    pub tot_lines: u64,
+    pub offset: u64,
+    pub next_offset: u64,
+    pub line_start: u64,
+    pub next_line: u64,
+    pub line_cnt: u64,
+    pub had_errors: bool,
+    pub eof: bool,
+    pub text: String,
 }
 
 
@@ -165,6 +753,9 @@ pub struct OptParams {
     pub take_n: Option<usize>,
     pub is_pretty: Option<bool>,
     pub cache_nm: Option<String>,
+    pub depth: Option<usize>,
+    pub max_nodes: Option<usize>,
+    pub filter: Option<String>,
 }
 
 
@@ -178,6 +769,9 @@ pub struct Params {
     pub take_n: Option<usize>,
     pub is_pretty: bool,
     pub cache_nm: Option<String>,
+    pub depth: usize,
+    pub max_nodes: Option<usize>,
+    pub filter: Option<String>,
 }
 
 impl Default for Params {
@@ -185,16 +779,20 @@ impl Default for Params {
         Params {
             path_str: String::from("."),
             meta_types: vec![MetaType::Sz, MetaType::Tm],
-            ordering: vec![OrdItem{nm: OrderBy::Dir, asc: OrderAsc::Asc}, OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc}],
+            ordering: vec![OrdItem{nm: OrderBy::Dir, asc: OrderAsc::Asc, cmp: None, locale: None}, OrdItem{nm: OrderBy::Nm, asc: OrderAsc::Asc, cmp: None, locale: None}],
             skip_n: None,
             take_n: Some(5),
             is_pretty: true,
             cache_nm: None,
+            depth: 1,
+            max_nodes: None,
+            filter: None,
         }
     }
 }
 
 
+#[cfg(windows)]
 use windows::core::Error as WinError;
 
 #[derive(Error, Debug)]
@@ -211,6 +809,10 @@ pub enum ApiError {
     #[error("Folder error: {0}")]
     Folder(String),
 
+    #[error("Watch error: {0}")]
+    Watch(#[from] notify::Error),
+
+    #[cfg(windows)]
     #[error("windows::core::Error: {0}")]
     DirApi(#[from] WinError),
 
@@ -230,6 +832,7 @@ impl From<ApiError> for NApiError {
                 NApiError::new(Status::Unknown, format!("JSON error: {}", e))
             }
 
+            #[cfg(windows)]
             ApiError::DirApi(e) => {
                 NApiError::new(Status::Unknown, format!("DirApi error: {}", e))
             }
@@ -237,6 +840,10 @@ impl From<ApiError> for NApiError {
             ApiError::Folder(msg) => {
                 NApiError::new(Status::Unknown, format!("Folder error: {}", msg))
             }
+
+            ApiError::Watch(e) => {
+                NApiError::new(Status::Unknown, format!("Watch error: {}", e))
+            }
             
         }
     }
@@ -0,0 +1,261 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::SystemTime;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use crate::models::{ApiError, FindHit, Item, NameIndexInfo};
+use crate::find::{Query, TopHits};
+use crate::system_time_ext::SystemTimeExt;
+use crate::text::{load_index, save_index};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndexEntry {
+    pub nm: String,
+    pub dir: bool,
+    /// 0 for folders
+    pub sz: u64,
+    pub tm: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndexDir {
+    /// mtime of the folder when `entries` were read
+    pub tm: SystemTime,
+    pub entries: Vec<IndexEntry>,
+}
+
+///
+/// persisted list of the files under a root, like `locate`
+///
+/// `dirs` maps `/` separated paths relative to `root` (`""` is the root) to their entries.
+/// `update` re-reads only the folders whose mtime changed. Adding, removing or renaming an entry
+/// changes the mtime of its folder, editing a file in place does not: its `sz`/`tm` are refreshed
+/// the next time the folder is read.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct NameIndex {
+    pub root: String,
+    /// seconds, when the index was updated
+    pub tm: u64,
+    pub dirs: HashMap<String, IndexDir>,
+}
+
+/// running totals of `NameIndex::update`, read by the progress reporter
+#[derive(Default)]
+pub struct IndexStat {
+    pub files: AtomicU64,
+    pub dirs: AtomicU64,
+    pub read_dirs: AtomicU64,
+}
+
+impl IndexStat {
+    pub fn to_info(&self, root: &str) -> NameIndexInfo {
+        NameIndexInfo {
+            root: root.to_string(),
+            files: self.files.load(Ordering::Relaxed),
+            dirs: self.dirs.load(Ordering::Relaxed),
+            read_dirs: Some(self.read_dirs.load(Ordering::Relaxed)),
+            ..NameIndexInfo::default()
+        }
+    }
+}
+
+impl NameIndex {
+    pub fn info(&self) -> NameIndexInfo {
+        NameIndexInfo {
+            root: self.root.clone(),
+            files: self.dirs.values()
+                .map(|dir| dir.entries.iter().filter(|entry| !entry.dir).count() as u64)
+                .sum(),
+            dirs: self.dirs.len() as u64,
+            tm: self.tm,
+            ..NameIndexInfo::default()
+        }
+    }
+
+    /// the saved index of `root`, `None` if there is none or it can't be read
+    pub fn load(root: &str, index_dir: &Path) -> Option<NameIndex> {
+        load_index(index_dir, root).filter(|idx: &NameIndex| idx.root == root)
+    }
+
+    pub fn save(&self, index_dir: &Path) -> Result<()> {
+        save_index(index_dir, &self.root, self)
+    }
+
+    ///
+    /// walk `root`, folders in parallel, reusing the folders of `prev` whose mtime is unchanged
+    ///
+    /// Symlinks are listed but not followed, other file systems mounted below `root` are not
+    /// entered (unix). Returns `None` if canceled.
+    pub fn update(root: &Path, prev: Option<&NameIndex>, stat: &IndexStat, cancel: &AtomicBool) -> Option<NameIndex> {
+        let updater = Updater {
+            prev,
+            stat,
+            cancel,
+            #[cfg(unix)]
+            dev: root.metadata().map(|meta| meta.dev()).unwrap_or_default(),
+            dirs: Mutex::new(HashMap::new()),
+        };
+        updater.update_dir(root, String::new());
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        Some(NameIndex {
+            root: root.to_string_lossy().into_owned(),
+            tm: SystemTime::now().to_sec(),
+            dirs: updater.dirs.into_inner().unwrap_or_default(),
+        })
+    }
+
+    /// the best `max_results` entries matching `query` ranked, and the count of all
+    pub fn search(&self, query: &Query, max_results: usize) -> (Vec<FindHit>, usize) {
+        self.dirs.par_iter()
+            .flat_map_iter(|(rel, dir)| dir.entries.iter().filter_map(move |entry| {
+                let sub_rel = if rel.is_empty() { entry.nm.clone() } else { format!("{}/{}", rel, entry.nm) };
+                let (score, hl) = query.match_rel(&entry.nm, &sub_rel)?;
+                Some(FindHit { rel: sub_rel, score, hl, item: entry.to_item() })
+            }))
+            .fold(|| TopHits::new(max_results), |mut top, hit| {
+                top.push(hit);
+                top
+            })
+            .reduce(|| TopHits::new(max_results), TopHits::merge)
+            .into_ranked()
+    }
+}
+
+impl IndexEntry {
+    fn to_item(&self) -> Item {
+        Item {
+            nm: self.nm.clone(),
+            dir: self.dir,
+            sz: (!self.dir).then_some(self.sz),
+            tm: Some(self.tm),
+            ..Item::default()
+        }
+    }
+}
+
+struct Updater<'a> {
+    prev: Option<&'a NameIndex>,
+    stat: &'a IndexStat,
+    cancel: &'a AtomicBool,
+    #[cfg(unix)]
+    dev: u64,
+    dirs: Mutex<HashMap<String, IndexDir>>,
+}
+
+impl Updater<'_> {
+    fn update_dir(&self, path: &Path, rel: String) {
+        if self.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(meta) = path.symlink_metadata() else {
+            return;
+        };
+        #[cfg(unix)]
+        if meta.dev() != self.dev {
+            return;
+        }
+        let Ok(tm) = meta.modified() else {
+            return;
+        };
+        let entries = match self.prev.and_then(|prev| prev.dirs.get(&rel)).filter(|dir| dir.tm == tm) {
+            Some(dir) => dir.entries.clone(),
+            None => {
+                let Some(entries) = read_entries(path) else {
+                    return;
+                };
+                self.stat.read_dirs.fetch_add(1, Ordering::Relaxed);
+                entries
+            }
+        };
+        self.stat.dirs.fetch_add(1, Ordering::Relaxed);
+        self.stat.files.fetch_add(entries.iter().filter(|entry| !entry.dir).count() as u64, Ordering::Relaxed);
+        let sub_dirs: Vec<(PathBuf, String)> = entries.iter()
+            .filter(|entry| entry.dir)
+            .map(|entry| {
+                let sub_rel = if rel.is_empty() { entry.nm.clone() } else { format!("{}/{}", rel, entry.nm) };
+                (path.join(&entry.nm), sub_rel)
+            })
+            .collect();
+        if let Ok(mut dirs) = self.dirs.lock() {
+            dirs.insert(rel, IndexDir { tm, entries });
+        }
+        sub_dirs.into_par_iter().for_each(|(sub_path, sub_rel)| self.update_dir(&sub_path, sub_rel));
+    }
+}
+
+/// entries of a folder, symlinks by their own metadata
+fn read_entries(path: &Path) -> Option<Vec<IndexEntry>> {
+    let entries = std::fs::read_dir(path).ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let meta = entry.metadata().ok()?;
+            let dir = meta.file_type().is_dir();
+            Some(IndexEntry {
+                nm: entry.file_name().to_string_lossy().into_owned(),
+                dir,
+                sz: if dir { 0 } else { meta.len() },
+                tm: meta.modified().map(|tm| tm.to_sec()).unwrap_or_default(),
+            })
+        })
+        .collect();
+    Some(entries)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::FindMode;
+
+    fn rels(idx: &NameIndex, query: &str, mode: FindMode) -> Vec<String> {
+        idx.search(&Query::new(query, mode).unwrap(), usize::MAX).0.into_iter().map(|hit| hit.rel).collect()
+    }
+
+    #[test]
+    fn test_name_index() {
+        let base = std::env::temp_dir().join("napi-folder-test-name-index");
+        let index_dir = base.join("index");
+        let root = base.join("root");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(root.join("photos/2024")).unwrap();
+        std::fs::create_dir_all(root.join("docs")).unwrap();
+        std::fs::write(root.join("photos/2024/beach.jpg"), "jpg").unwrap();
+        std::fs::write(root.join("docs/report.txt"), "report").unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let stat = IndexStat::default();
+        let idx = NameIndex::update(&root, None, &stat, &cancel).unwrap();
+        assert_eq!(stat.read_dirs.load(Ordering::Relaxed), 4);
+        let info = idx.info();
+        assert_eq!((info.dirs, info.files), (4, 2));
+        assert_eq!(rels(&idx, "BEACH", FindMode::Substring), vec!["photos/2024/beach.jpg"]);
+        assert_eq!(rels(&idx, "rprt", FindMode::Fuzzy), vec!["docs/report.txt"]);
+        let hit = &idx.search(&Query::new("report", FindMode::Substring).unwrap(), 1).0[0];
+        assert_eq!(hit.item.sz, Some(6));
+        assert_eq!(hit.hl, vec![(5, 11)]);
+
+        idx.save(&index_dir).unwrap();
+        let saved = NameIndex::load(&idx.root, &index_dir).unwrap();
+        assert_eq!(saved.dirs, idx.dirs);
+
+        // only the folder that got a new entry is read again
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(root.join("docs/notes.txt"), "").unwrap();
+        let stat = IndexStat::default();
+        let idx = NameIndex::update(&root, Some(&saved), &stat, &cancel).unwrap();
+        assert_eq!(stat.read_dirs.load(Ordering::Relaxed), 1);
+        assert_eq!(rels(&idx, "notes", FindMode::Substring), vec!["docs/notes.txt"]);
+
+        cancel.store(true, Ordering::Relaxed);
+        assert!(NameIndex::update(&root, Some(&idx), &IndexStat::default(), &cancel).is_none());
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
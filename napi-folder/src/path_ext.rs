@@ -1,17 +1,25 @@
 use std::path::Component::RootDir;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static TMP_CNT: AtomicU64 = AtomicU64::new(0);
 
 pub trait PathExt {
     fn is_root(&self) -> bool;
+    /// `.<name>.<pid>.<n>.tmp` next to the path, unique per call so concurrent writers don't collide
+    fn tmp_sibling(&self) -> PathBuf;
     // fn has_children(&self) -> bool;
     // fn get_cnt(&self) -> Option<usize>;
 }
 impl PathExt for Path {
     fn is_root(&self) -> bool {
-        match self.components().last() {
-            Some(RootDir) => true,
-            _ => false,
-        }
+        matches!(self.components().next_back(), Some(RootDir))
+    }
+
+    fn tmp_sibling(&self) -> PathBuf {
+        let nm = self.file_name().map(|nm| nm.to_string_lossy().into_owned()).unwrap_or_default();
+        let n = TMP_CNT.fetch_add(1, Ordering::Relaxed);
+        self.with_file_name(format!(".{}.{}.{}.tmp", nm, std::process::id(), n))
     }
 
     // fn has_children(&self) -> bool {
@@ -0,0 +1,338 @@
+use std::io::{BufRead, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use encoding_rs::{Encoding, UTF_8};
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+use crate::models::{ApiError, SearchHit, GrepFile, GrepSummary};
+use crate::filter::glob_match;
+use crate::text::{detect_bytes, DETECT_SZ};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// chars kept on each side of a match in `SearchHit.snippet`
+const SNIPPET_CTX: usize = 60;
+/// a NUL in this many first bytes marks a binary file
+const BINARY_SNIFF_SZ: usize = 8 * 1024;
+
+/// a literal or regex pattern, matched per line
+pub struct Matcher {
+    re: Regex,
+}
+
+impl Matcher {
+    pub fn new(pattern: &str, regex: bool, case_sensitive: bool) -> Result<Matcher> {
+        if pattern.is_empty() {
+            return Err(ApiError::Folder(String::from("Err Search: empty pattern")));
+        }
+        let pattern = if regex { pattern.to_string() } else { regex::escape(pattern) };
+        let re = RegexBuilder::new(&pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map_err(|err| ApiError::Folder(format!("Err Regex: {}", err)))?;
+        Ok(Matcher { re })
+    }
+
+    /// byte ranges of the non-empty matches in `s`
+    pub fn find_ranges(&self, s: &str) -> Vec<(usize, usize)> {
+        self.re.find_iter(s)
+            .filter(|m| !m.is_empty())
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+///
+/// matches of `matcher` in the lines of `reader`, decoded with `encoding`
+///
+/// Reads a line at a time, so files of any size are fine. Stops after `max_hits`;
+/// the second value is `true` if there were more.
+pub fn search_lines<R: BufRead>(mut reader: R, encoding: &'static Encoding, matcher: &Matcher, max_hits: usize) -> Result<(Vec<SearchHit>, bool)> {
+    let mut hits = vec![];
+    let mut buf: Vec<u8> = vec![];
+    let mut offset: u64 = 0;
+    let mut line_no: u64 = 0;
+    loop {
+        buf.clear();
+        let n = reader.read_until(b'\n', &mut buf)?;
+        if n == 0 {
+            return Ok((hits, false));
+        }
+        let mut bytes = buf.as_slice();
+        while let [rest @ .., b'\n' | b'\r'] = bytes {
+            bytes = rest;
+        }
+        let mut bom_len = 0;
+        if line_no == 0 {
+            if let Some((bom_encoding, len)) = Encoding::for_bom(bytes) {
+                if bom_encoding == encoding {
+                    bom_len = len;
+                }
+            }
+        }
+        let (line, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        for m in matcher.re.find_iter(&line) {
+            if m.is_empty() {
+                continue;
+            }
+            if hits.len() >= max_hits {
+                return Ok((hits, true));
+            }
+            let byte_col = if encoding == UTF_8 {
+                m.start()
+            } else {
+                encoding.encode(&line[..m.start()]).0.len()
+            };
+            hits.push(to_hit(&line, line_no, offset + (bom_len + byte_col) as u64, m.start(), m.end()));
+        }
+        offset += n as u64;
+        line_no += 1;
+    }
+}
+
+/// `start`, `end`: byte range of the match in `line`
+fn to_hit(line: &str, line_no: u64, offset: u64, start: usize, end: usize) -> SearchHit {
+    let col = line[..start].chars().count();
+    let len = line[start..end].chars().count();
+    let snippet_start = col.saturating_sub(SNIPPET_CTX);
+    let snippet: String = line.chars()
+        .skip(snippet_start)
+        .take(col - snippet_start + len + SNIPPET_CTX)
+        .collect();
+    SearchHit {
+        line_no,
+        offset,
+        col,
+        len,
+        snippet,
+        hl: (col - snippet_start, col - snippet_start + len),
+    }
+}
+
+
+///
+/// content search over a folder tree, folders and files in parallel
+///
+/// Binaries (known to `infer` or with a NUL near the start) are skipped, other files are decoded with
+/// the encoding `chardetng` guesses. Hidden entries are skipped unless `hidden`.
+pub struct Grep<'a> {
+    pub matcher: Matcher,
+    /// file name globs, or relative path globs if they have a `/`; all files if empty
+    pub include: Vec<String>,
+    /// like `include`, matching folders are not entered
+    pub exclude: Vec<String>,
+    pub hidden: bool,
+    pub max_hits: u64,
+    pub max_file_hits: usize,
+    pub cancel: &'a AtomicBool,
+    pub on_file: &'a (dyn Fn(GrepFile) + Sync),
+    pub files: AtomicU64,
+    pub matched_files: AtomicU64,
+    pub hits: AtomicU64,
+    pub binaries: AtomicU64,
+    pub truncated: AtomicBool,
+}
+
+impl<'a> Grep<'a> {
+    pub fn new(matcher: Matcher, cancel: &'a AtomicBool, on_file: &'a (dyn Fn(GrepFile) + Sync)) -> Self {
+        Grep {
+            matcher,
+            include: vec![],
+            exclude: vec![],
+            hidden: false,
+            max_hits: u64::MAX,
+            max_file_hits: usize::MAX,
+            cancel,
+            on_file,
+            files: AtomicU64::new(0),
+            matched_files: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            binaries: AtomicU64::new(0),
+            truncated: AtomicBool::new(false),
+        }
+    }
+
+    fn stopped(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) || self.truncated.load(Ordering::Relaxed)
+    }
+
+    pub fn run(&self, root: &Path) -> GrepSummary {
+        self.grep_dir(root, "");
+        GrepSummary {
+            root: root.to_string_lossy().into_owned(),
+            files: self.files.load(Ordering::Relaxed),
+            matched_files: self.matched_files.load(Ordering::Relaxed),
+            hits: self.hits.load(Ordering::Relaxed).min(self.max_hits),
+            binaries: self.binaries.load(Ordering::Relaxed),
+            truncated: self.truncated.load(Ordering::Relaxed),
+            canceled: self.cancel.load(Ordering::Relaxed).then_some(true),
+        }
+    }
+
+    fn grep_dir(&self, dir: &Path, rel: &str) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        let entries: Vec<(std::path::PathBuf, String, String, bool)> = entries.flatten()
+            .filter_map(|entry| {
+                let file_type = entry.file_type().ok()?;
+                if file_type.is_symlink() {
+                    return None;
+                }
+                let nm = entry.file_name().to_string_lossy().into_owned();
+                if !self.hidden && nm.starts_with('.') {
+                    return None;
+                }
+                let sub_rel = if rel.is_empty() { nm.clone() } else { format!("{}/{}", rel, nm) };
+                if self.exclude.iter().any(|pattern| glob_hit(pattern, &nm, &sub_rel)) {
+                    return None;
+                }
+                Some((entry.path(), nm, sub_rel, file_type.is_dir()))
+            })
+            .collect();
+        entries.par_iter().for_each(|(path, nm, sub_rel, dir)| {
+            if self.stopped() {
+                return;
+            }
+            if *dir {
+                self.grep_dir(path, sub_rel);
+            } else if self.include.is_empty() || self.include.iter().any(|pattern| glob_hit(pattern, nm, sub_rel)) {
+                self.grep_file(path, sub_rel);
+            }
+        });
+    }
+
+    fn grep_file(&self, path: &Path, rel: &str) {
+        let Ok(mut file) = std::fs::File::open(path) else {
+            return;
+        };
+        let mut sample = vec![];
+        if (&mut file).take(DETECT_SZ as u64).read_to_end(&mut sample).is_err() {
+            return;
+        }
+        self.files.fetch_add(1, Ordering::Relaxed);
+        if is_binary(&sample) {
+            self.binaries.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let encoding = detect_bytes(&sample, sample.len() < DETECT_SZ);
+        let reader = std::io::BufReader::new(std::io::Cursor::new(sample).chain(file));
+        let Ok((mut hits, mut truncated)) = search_lines(reader, encoding, &self.matcher, self.max_file_hits) else {
+            return;
+        };
+        if hits.is_empty() {
+            return;
+        }
+        let total = self.hits.fetch_add(hits.len() as u64, Ordering::Relaxed);
+        if total + hits.len() as u64 > self.max_hits {
+            hits.truncate(self.max_hits.saturating_sub(total) as usize);
+            truncated = true;
+            self.truncated.store(true, Ordering::Relaxed);
+            if hits.is_empty() {
+                return;
+            }
+        }
+        self.matched_files.fetch_add(1, Ordering::Relaxed);
+        (self.on_file)(GrepFile {
+            path: path.to_string_lossy().into_owned(),
+            rel: rel.to_string(),
+            enc: encoding.name().to_string(),
+            hits,
+            truncated,
+        });
+    }
+}
+
+/// `pattern` with a `/` is matched against the relative path, else against the name, case-insensitive
+pub fn glob_hit(pattern: &str, nm: &str, rel: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    if pattern.contains('/') {
+        glob_match(&pattern, &rel.to_lowercase())
+    } else {
+        glob_match(&pattern, &nm.to_lowercase())
+    }
+}
+
+/// a NUL near the start, or a signature of a non-text format
+pub fn is_binary(sample: &[u8]) -> bool {
+    if memchr::memchr(0, &sample[..sample.len().min(BINARY_SNIFF_SZ)]).is_some() {
+        return true;
+    }
+    infer::get(sample).is_some_and(|kind| kind.matcher_type() != infer::MatcherType::Text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use encoding_rs::EUC_KR;
+
+    #[test]
+    fn test_search_lines() {
+        let text = "\u{feff}first line\r\nsecond Line, line\n\nlast";
+        let matcher = Matcher::new("line", false, false).unwrap();
+        let (hits, more) = search_lines(text.as_bytes(), UTF_8, &matcher, 10).unwrap();
+        assert!(!more);
+        let pos: Vec<(u64, u64, usize)> = hits.iter().map(|hit| (hit.line_no, hit.offset, hit.col)).collect();
+        assert_eq!(pos, vec![(0, 9, 6), (1, 22, 7), (1, 28, 13)]);
+        assert_eq!(hits[1].hl, (7, 11));
+
+        let matcher = Matcher::new("line", false, true).unwrap();
+        assert_eq!(search_lines(text.as_bytes(), UTF_8, &matcher, 10).unwrap().0.len(), 2);
+        let (hits, more) = search_lines(text.as_bytes(), UTF_8, &matcher, 1).unwrap();
+        assert_eq!((hits.len(), more), (1, true));
+
+        let (euc, _, _) = EUC_KR.encode("가나다 abc\n라 a1c");
+        let matcher = Matcher::new(r"a\dc|다", true, true).unwrap();
+        let hits = search_lines(euc.as_ref(), EUC_KR, &matcher, 10).unwrap().0;
+        let pos: Vec<(u64, u64, usize)> = hits.iter().map(|hit| (hit.line_no, hit.offset, hit.col)).collect();
+        assert_eq!(pos, vec![(0, 4, 2), (1, 14, 2)]);
+
+        assert!(Matcher::new("(", true, true).is_err());
+    }
+
+    #[test]
+    fn test_grep() {
+        let base = std::env::temp_dir().join("napi-folder-test-grep");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(base.join("src/sub")).unwrap();
+        std::fs::create_dir_all(base.join("target")).unwrap();
+        std::fs::create_dir_all(base.join(".git")).unwrap();
+        std::fs::write(base.join("src/a.rs"), "fn todo() {}\n// TODO: x\n").unwrap();
+        std::fs::write(base.join("src/sub/b.txt"), EUC_KR.encode(&format!("할 일: TODO\n{}", "문서를 정리합니다.\n".repeat(4))).0.as_ref()).unwrap();
+        std::fs::write(base.join("src/c.bin"), b"TODO\x00\x01").unwrap();
+        std::fs::write(base.join("target/d.rs"), "TODO").unwrap();
+        std::fs::write(base.join(".git/e.rs"), "TODO").unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let found = std::sync::Mutex::new(vec![]);
+        let on_file = |file: GrepFile| found.lock().unwrap().push(file);
+        let mut grep = Grep::new(Matcher::new("todo", false, true).unwrap(), &cancel, &on_file);
+        grep.exclude = vec![String::from("target")];
+        let summary = grep.run(&base);
+        assert_eq!((summary.files, summary.matched_files, summary.hits, summary.binaries), (3, 1, 1, 1));
+
+        let mut grep = Grep::new(Matcher::new("todo", false, false).unwrap(), &cancel, &on_file);
+        grep.include = vec![String::from("*.txt"), String::from("src/*.rs")];
+        found.lock().unwrap().clear();
+        grep.run(&base);
+        let mut found: Vec<(String, String, usize)> = found.into_inner().unwrap().into_iter()
+            .map(|file| (file.rel, file.enc, file.hits.len()))
+            .collect();
+        found.sort();
+        assert_eq!(found, vec![
+            (String::from("src/a.rs"), String::from("UTF-8"), 2),
+            (String::from("src/sub/b.txt"), String::from("EUC-KR"), 1),
+        ]);
+
+        // truncated only once a hit is left out
+        let on_file = |_| {};
+        for (max_hits, truncated) in [(2, false), (1, true)] {
+            let mut grep = Grep::new(Matcher::new("todo", false, false).unwrap(), &cancel, &on_file);
+            grep.include = vec![String::from("src/*.rs")];
+            grep.max_hits = max_hits;
+            let summary = grep.run(&base);
+            assert_eq!((summary.hits, summary.truncated), (max_hits, truncated));
+        }
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
@@ -1,15 +1,21 @@
 pub trait SystemTimeExt {
     fn to_sec(&self) -> u64;
+    /// nanoseconds since the epoch, 0 before it
+    fn to_ns(&self) -> u128;
     // fn to_ms(&self) -> u128;
 }
 
 impl SystemTimeExt for std::time::SystemTime {
     fn to_sec(&self) -> u64 {
+        // clamp like FILETIME conversion does, unix file systems can hold pre-epoch times
         match self.duration_since(std::time::UNIX_EPOCH) {
             Ok(dur) => dur.as_secs(),
-            Err(_) => panic!("SystemTime before UNIX EPOCH!"),
+            Err(_) => 0,
         }
     }
+    fn to_ns(&self) -> u128 {
+        self.duration_since(std::time::UNIX_EPOCH).map(|dur| dur.as_nanos()).unwrap_or(0)
+    }
     // fn to_ms(&self) -> u128 {
     //     self.duration_since(std::time::UNIX_EPOCH).unwrap().as_millis()
     // }   
@@ -0,0 +1,916 @@
+use std::fs::File;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use chardetng::EncodingDetector;
+use encoding_rs::{Decoder, DecoderResult, Encoding, BIG5, EUC_JP, EUC_KR, GB18030, GBK, SHIFT_JIS, UTF_8, UTF_16BE, UTF_16LE};
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+use crate::models::{ApiError, TextPage, TailEvent, TailKind, EncCandidate, LineEnding, TextStats, Indent};
+use crate::metadata_ext::MetaExt;
+use crate::system_time_ext::SystemTimeExt;
+use crate::path_ext::PathExt;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// a checkpoint is kept every `LINE_STEP` lines
+pub const LINE_STEP: u64 = 1024;
+/// bytes fed to `chardetng` when the index is built
+pub const DETECT_SZ: usize = 64 * 1024;
+const CHUNK_SZ: usize = 1024 * 1024;
+/// bytes looked at around an offset to find a character start
+const SNAP_SZ: usize = 256;
+/// bytes before the indexed size hashed to tell an appended file from a rewritten one
+const TAIL_SZ: u64 = 4096;
+pub const DEFAULT_PAGE_BYTES: usize = 1024 * 1024;
+
+///
+/// sparse line-offset index of a text file
+///
+/// `checkpoints[k]` is the byte offset of line `k * LINE_STEP`. Saved as json under the
+/// cache dir and extended in place when the file only grew (logs): the file is larger and the
+/// last bytes of the indexed part still hash to `tail_hash`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LineIndex {
+    pub path: String,
+    pub sz: u64,
+    pub tm: u64,
+    pub enc: String,
+    pub nl_cnt: u64,
+    pub ends_with_nl: bool,
+    pub checkpoints: Vec<u64>,
+    /// hash of the `TAIL_SZ` bytes before `sz`
+    #[serde(default)]
+    pub tail_hash: u64,
+}
+
+impl LineIndex {
+    pub fn encoding(&self) -> &'static Encoding {
+        Encoding::for_label(self.enc.as_bytes()).unwrap_or(UTF_8)
+    }
+
+    /// lines in the file, the last line does not need a newline
+    pub fn line_cnt(&self) -> u64 {
+        if self.sz == 0 {
+            0
+        } else if self.ends_with_nl {
+            self.nl_cnt
+        } else {
+            self.nl_cnt + 1
+        }
+    }
+
+    pub fn is_valid(&self, sz: u64, tm: u64) -> bool {
+        self.sz == sz && self.tm == tm
+    }
+
+    ///
+    /// load the saved index of `path` or build it
+    ///
+    /// `prev` (the in-memory copy) is tried before the saved one.
+    /// An index of a file that only grew is extended from where it stopped.
+    pub fn load_or_build(path: &Path, index_dir: &Path, prev: Option<LineIndex>) -> Result<LineIndex> {
+        let metadata = path.metadata()?;
+        let sz = metadata.len();
+        let tm = metadata.modified()?.to_sec();
+        let path_str = path.to_string_lossy().into_owned();
+
+        let saved: Option<LineIndex> = prev
+            .or_else(|| load_index(index_dir, &path_str))
+            .filter(|idx: &LineIndex| idx.path == path_str);
+        let same_tail = |idx: &LineIndex| tail_hash(path, idx.sz).ok() == Some(idx.tail_hash);
+        let mut idx = match saved {
+            Some(idx) if idx.is_valid(sz, tm) && same_tail(&idx) => return Ok(idx),
+            Some(idx) if idx.sz < sz && idx.sz > 0 && same_tail(&idx) => idx,
+            _ => LineIndex {
+                path: path_str,
+                sz: 0,
+                tm,
+                enc: detect_encoding(path)?.name().to_string(),
+                nl_cnt: 0,
+                ends_with_nl: false,
+                checkpoints: vec![0],
+                tail_hash: 0,
+            },
+        };
+        idx.extend(path, sz)?;
+        idx.tm = tm;
+        idx.tail_hash = tail_hash(path, idx.sz)?;
+        let _ = save_index(index_dir, &idx.path, &idx);
+        Ok(idx)
+    }
+
+    /// scan from `self.sz` to `sz`
+    fn extend(&mut self, path: &Path, sz: u64) -> Result<()> {
+        let mut file = File::open(path)?;
+        file.seek(SeekFrom::Start(self.sz))?;
+        let mut buf = vec![0u8; CHUNK_SZ];
+        let mut pos = self.sz;
+        while pos < sz {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            for i in memchr::memchr_iter(b'\n', &buf[..n]) {
+                self.nl_cnt += 1;
+                if self.nl_cnt.is_multiple_of(LINE_STEP) {
+                    self.checkpoints.push(pos + i as u64 + 1);
+                }
+            }
+            self.ends_with_nl = buf[n - 1] == b'\n';
+            pos += n as u64;
+        }
+        self.sz = pos;
+        Ok(())
+    }
+
+    /// byte offset of line `line_no` (0 based), scanning from the nearest checkpoint
+    pub fn line_offset(&self, file: &mut File, line_no: u64) -> Result<u64> {
+        let cp = ((line_no / LINE_STEP) as usize).min(self.checkpoints.len() - 1);
+        let mut pos = self.checkpoints[cp];
+        let mut remain = line_no - cp as u64 * LINE_STEP;
+        if remain == 0 {
+            return Ok(pos);
+        }
+        file.seek(SeekFrom::Start(pos))?;
+        let mut buf = vec![0u8; CHUNK_SZ];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                return Ok(self.sz);
+            }
+            for i in memchr::memchr_iter(b'\n', &buf[..n]) {
+                remain -= 1;
+                if remain == 0 {
+                    return Ok(pos + i as u64 + 1);
+                }
+            }
+            pos += n as u64;
+        }
+    }
+
+    /// `(line_no, line_offset)` of the line containing byte `offset`
+    pub fn line_at(&self, file: &mut File, offset: u64) -> Result<(u64, u64)> {
+        let cp = match self.checkpoints.binary_search(&offset) {
+            Ok(i) => return Ok((i as u64 * LINE_STEP, offset)),
+            Err(i) => i - 1,
+        };
+        let mut line_no = cp as u64 * LINE_STEP;
+        let mut line_offset = self.checkpoints[cp];
+        let mut pos = line_offset;
+        file.seek(SeekFrom::Start(pos))?;
+        let mut buf = vec![0u8; CHUNK_SZ];
+        while pos < offset {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            let end = n.min((offset - pos) as usize);
+            for i in memchr::memchr_iter(b'\n', &buf[..end]) {
+                line_no += 1;
+                line_offset = pos + i as u64 + 1;
+            }
+            pos += n as u64;
+        }
+        Ok((line_no, line_offset))
+    }
+}
+
+/// hash of the up to `TAIL_SZ` bytes of `path` before `end`
+fn tail_hash(path: &Path, end: u64) -> Result<u64> {
+    let mut file = File::open(path)?;
+    let start = end.saturating_sub(TAIL_SZ);
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = vec![];
+    file.take(end - start).read_to_end(&mut buf)?;
+    let mut hasher = DefaultHasher::new();
+    buf.hash(&mut hasher);
+    Ok(hasher.finish())
+}
+
+/// `<hash of path_str>.json` in `index_dir`
+pub fn index_file(index_dir: &Path, path_str: &str) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    path_str.hash(&mut hasher);
+    index_dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+/// the index saved for `path_str`, `None` if there is none or it can't be read
+pub fn load_index<T: DeserializeOwned>(index_dir: &Path, path_str: &str) -> Option<T> {
+    let bytes = std::fs::read(index_file(index_dir, path_str)).ok()?;
+    serde_json::from_slice(&bytes).ok()
+}
+
+/// written to a tmp file and renamed, concurrent saves of the same index don't mix
+pub fn save_index<T: Serialize>(index_dir: &Path, path_str: &str, idx: &T) -> Result<()> {
+    std::fs::create_dir_all(index_dir)?;
+    let index_path = index_file(index_dir, path_str);
+    let tmp_path = index_path.tmp_sibling();
+    std::fs::write(&tmp_path, serde_json::to_vec(idx)?)?;
+    std::fs::rename(&tmp_path, &index_path)?;
+    Ok(())
+}
+
+/// a byte order mark decides, else a guess from the first `DETECT_SZ` bytes; non ASCII-compatible guesses fall back to UTF-8
+pub fn detect_encoding(path: &Path) -> Result<&'static Encoding> {
+    let mut file = File::open(path)?;
+    let mut sample = vec![0u8; DETECT_SZ];
+    let n = read_full(&mut file, &mut sample)?;
+    sample.truncate(n);
+    Ok(detect_bytes(&sample, n < DETECT_SZ))
+}
+
+/// `detect_encoding` of a sample, `last` if it is the whole file
+pub fn detect_bytes(sample: &[u8], last: bool) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(sample) {
+        return encoding;
+    }
+    if sample.is_empty() {
+        return UTF_8;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(sample, last);
+    let encoding = detector.guess(None, true);
+    if encoding.is_ascii_compatible() { encoding } else { UTF_8 }
+}
+
+/// top-level domains whose `chardetng` guesses are offered as candidates
+const CANDIDATE_TLDS: [&[u8]; 10] = [b"kr", b"jp", b"cn", b"tw", b"ru", b"gr", b"tr", b"il", b"pl", b"vn"];
+const MAX_CANDIDATES: usize = 5;
+
+///
+/// the encoding to decode `bytes` with, whether it is confident and the encodings worth a "reopen with"
+///
+/// `chardetng` drops an encoding on the first malformed sequence, so a slightly broken CP949 file
+/// comes back as windows-1252. A multibyte encoding that decodes with errors in at most 1% of the
+/// non-ASCII bytes, and is the guess once those are taken out, is taken over a single byte guess,
+/// not confident. A byte order mark is taken as it is, `chardetng` never guesses UTF-16.
+/// The candidates start with the chosen encoding, the rest by fewest errors.
+/// `last`: `bytes` is the whole file, not a sample of its start.
+pub fn detect_candidates(bytes: &[u8], last: bool) -> (&'static Encoding, bool, Vec<EncCandidate>) {
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, last);
+    let (guess, mut confident) = detector.guess_assess(None, true);
+    let mut encodings: Vec<&'static Encoding> = vec![guess, UTF_8];
+    for tld in CANDIDATE_TLDS {
+        encodings.push(detector.guess(Some(tld), false));
+    }
+    encodings.extend([EUC_KR, SHIFT_JIS, GBK, BIG5, EUC_JP]);
+    let mut candidates: Vec<(&'static Encoding, usize)> = vec![];
+    for encoding in encodings {
+        if encoding.is_ascii_compatible() && !candidates.iter().any(|(e, _)| *e == encoding) {
+            candidates.push((encoding, count_errors(encoding, bytes, last)));
+        }
+    }
+    candidates.sort_by_key(|(_, err_cnt)| *err_cnt);
+
+    let mut chosen = guess;
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        chosen = encoding;
+        confident = true;
+    } else if lead_limit(guess).is_none() && guess != UTF_8 {
+        let non_ascii = bytes.iter().filter(|b| !b.is_ascii()).count();
+        let multibyte = candidates.iter()
+            .filter(|(encoding, err_cnt)| (*encoding == UTF_8 || lead_limit(encoding).is_some()) && err_cnt * 100 <= non_ascii)
+            .find(|(encoding, _)| redetect(encoding, bytes, last) == *encoding);
+        if let Some((encoding, _)) = multibyte {
+            chosen = encoding;
+            confident = false;
+        }
+    }
+    let first = match candidates.iter().position(|(encoding, _)| *encoding == chosen) {
+        Some(pos) => candidates.remove(pos),
+        None => (chosen, count_errors(chosen, bytes, last)),
+    };
+    candidates.insert(0, first);
+    let candidates = candidates.into_iter()
+        .take(MAX_CANDIDATES)
+        .map(|(encoding, err_cnt)| EncCandidate { enc: encoding.name().to_string(), err_cnt })
+        .collect();
+    (chosen, confident, candidates)
+}
+
+/// `chardetng` guess for `bytes` with the sequences `encoding` can not decode taken out
+fn redetect(encoding: &'static Encoding, bytes: &[u8], last: bool) -> &'static Encoding {
+    let text = encoding.decode_without_bom_handling(bytes).0.replace('\u{fffd}', "");
+    let (cleaned, _, _) = encoding.encode(&text);
+    let mut detector = EncodingDetector::new();
+    detector.feed(&cleaned, last);
+    detector.guess(None, true)
+}
+
+/// malformed sequences in `bytes` decoded with `encoding`, a cut sequence at the end counts unless `last`
+pub fn count_errors(encoding: &'static Encoding, bytes: &[u8], last: bool) -> usize {
+    let mut decoder = encoding.new_decoder_without_bom_handling();
+    let mut out = String::with_capacity(64 * 1024);
+    let mut pos = 0;
+    let mut cnt = 0;
+    loop {
+        out.clear();
+        let (result, read) = decoder.decode_to_string_without_replacement(&bytes[pos..], &mut out, last);
+        pos += read;
+        match result {
+            DecoderResult::InputEmpty => return cnt,
+            DecoderResult::Malformed(..) => cnt += 1,
+            DecoderResult::OutputFull => {}
+        }
+    }
+}
+
+/// `(LF, CRLF, CR)` line breaks
+fn count_line_endings(text: &str) -> (usize, usize, usize) {
+    let bytes = text.as_bytes();
+    let (mut lf, mut crlf, mut cr) = (0usize, 0usize, 0usize);
+    for i in memchr::memchr2_iter(b'\r', b'\n', bytes) {
+        match bytes[i] {
+            b'\n' if i > 0 && bytes[i - 1] == b'\r' => crlf += 1,
+            b'\n' => lf += 1,
+            _ if bytes.get(i + 1) == Some(&b'\n') => {}
+            _ => cr += 1,
+        }
+    }
+    (lf, crlf, cr)
+}
+
+/// the most used line ending, `None` without line breaks
+pub fn detect_line_ending(text: &str) -> Option<LineEnding> {
+    let (lf, crlf, cr) = count_line_endings(text);
+    if lf + crlf + cr == 0 {
+        None
+    } else if crlf >= lf && crlf >= cr {
+        Some(LineEnding::Crlf)
+    } else if lf >= cr {
+        Some(LineEnding::Lf)
+    } else {
+        Some(LineEnding::Cr)
+    }
+}
+
+///
+/// counts for the status bar, lines as an editor shows them
+///
+/// Lines end at LF or CRLF, or at CR in a text without LF. `read_text_range` only splits at LF,
+/// so a CR-only file is one line there.
+pub fn text_stats(text: &str) -> TextStats {
+    let (lf, crlf, cr) = count_line_endings(text);
+    let mut stats = TextStats {
+        char_cnt: text.chars().count(),
+        word_cnt: text.split_whitespace().count(),
+        mixed_ending: [lf, crlf, cr].iter().filter(|&&n| n > 0).count() > 1,
+        ends_with_nl: text.ends_with(['\n', '\r']),
+        ..TextStats::default()
+    };
+    let lines: Box<dyn Iterator<Item = &str>> = if cr > 0 && lf + crlf == 0 {
+        Box::new(text.strip_suffix('\r').unwrap_or(text).split('\r'))
+    } else {
+        Box::new(text.lines())
+    };
+    for (line_no, line) in lines.enumerate() {
+        stats.line_cnt += 1;
+        let len = line.chars().count();
+        if len > stats.longest_line {
+            stats.longest_line = len;
+            stats.longest_line_no = line_no;
+        }
+        if line.ends_with([' ', '\t']) {
+            stats.trailing_ws_lines += 1;
+        }
+        if !line.trim().is_empty() {
+            match line.chars().next() {
+                Some('\t') => stats.tab_lines += 1,
+                Some(' ') => stats.space_lines += 1,
+                _ => {}
+            }
+        }
+    }
+    stats.indent = match (stats.tab_lines, stats.space_lines) {
+        (0, 0) => None,
+        (_, 0) => Some(Indent::Tabs),
+        (0, _) => Some(Indent::Spaces),
+        _ => Some(Indent::Mixed),
+    };
+    stats
+}
+
+/// every line break of `text` as `line_ending`
+pub fn convert_line_ending(text: &str, line_ending: LineEnding) -> String {
+    let lf = text.replace("\r\n", "\n").replace('\r', "\n");
+    match line_ending {
+        LineEnding::Lf => lf,
+        _ => lf.replace('\n', line_ending.as_str()),
+    }
+}
+
+///
+/// `text` as bytes of `encoding`, with its byte order mark if `bom`
+///
+/// UTF-16 is encoded here, `encoding_rs` only decodes it. Characters `encoding` has no mapping
+/// for are an error, not `&#...;` like `encoding_rs` writes.
+pub fn encode_text(text: &str, encoding: &'static Encoding, bom: bool) -> Result<Vec<u8>> {
+    let mut bytes: Vec<u8> = vec![];
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        if bom {
+            bytes.extend(to_utf16(encoding, '\u{feff}'.encode_utf16(&mut [0; 1])));
+        }
+        for c in text.chars() {
+            bytes.extend(to_utf16(encoding, c.encode_utf16(&mut [0; 2])));
+        }
+        return Ok(bytes);
+    }
+    if bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xef\xbb\xbf");
+    }
+    let (encoded, _, unmappable) = encoding.encode(text);
+    if unmappable {
+        let c = text.chars().find(|c| {
+            let mut buf = [0; 4];
+            encoding.encode(c.encode_utf8(&mut buf)).2
+        }).unwrap_or('?');
+        return Err(ApiError::Folder(format!("Err Encoding: `{}` can not encode `{}`", encoding.name(), c)));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+fn to_utf16(encoding: &'static Encoding, units: &[u16]) -> Vec<u8> {
+    units.iter()
+        .flat_map(|unit| if encoding == UTF_16LE { unit.to_le_bytes() } else { unit.to_be_bytes() })
+        .collect()
+}
+
+fn read_full(file: &mut File, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        let read = file.read(&mut buf[n..])?;
+        if read == 0 {
+            break;
+        }
+        n += read;
+    }
+    Ok(n)
+}
+
+///
+/// bytes below this never continue a multibyte character, `None` for UTF-8 and single byte encodings
+///
+/// EUC trail bytes are >= 0xA1, Shift_JIS/Big5 >= 0x40, the GB18030 four byte form uses 0x30..=0x39.
+fn lead_limit(encoding: &'static Encoding) -> Option<u8> {
+    if encoding == EUC_KR || encoding == EUC_JP {
+        Some(0x80)
+    } else if encoding == SHIFT_JIS || encoding == BIG5 {
+        Some(0x40)
+    } else if encoding == GBK || encoding == GB18030 {
+        Some(0x30)
+    } else {
+        None
+    }
+}
+
+/// byte length of the legacy multibyte character starting `bytes`
+fn char_len(bytes: &[u8], encoding: &'static Encoding) -> usize {
+    let b = bytes[0];
+    if b < 0x80 {
+        1
+    } else if encoding == SHIFT_JIS {
+        if (0xA1..=0xDF).contains(&b) { 1 } else { 2 }
+    } else if encoding == EUC_JP && b == 0x8F {
+        3
+    } else if encoding == GB18030 && bytes.get(1).is_some_and(|b| (0x30..=0x39).contains(b)) {
+        4
+    } else {
+        2
+    }
+}
+
+///
+/// `(start of the last character before pos, first character start at or after pos)`
+///
+/// Steps through the characters from `anchor`, which has to be a known boundary, i.e. the position after a byte below `lead_limit`.
+fn step_chars(buf: &[u8], anchor: usize, pos: usize, encoding: &'static Encoding) -> (usize, usize) {
+    let mut prev = anchor;
+    let mut p = anchor;
+    while p < pos {
+        prev = p;
+        p += char_len(&buf[p..], encoding);
+    }
+    (prev, p)
+}
+
+/// position in `buf` where a character starts, at or before `pos`; `buf` has to start with a character
+pub fn char_boundary_before(buf: &[u8], pos: usize, encoding: &'static Encoding) -> usize {
+    let pos = pos.min(buf.len());
+    if encoding == UTF_8 {
+        if pos == buf.len() {
+            // a sequence cut off at the end of `buf`
+            let Some(lead) = buf.iter().rposition(|&b| (b & 0xC0) != 0x80) else {
+                return pos;
+            };
+            let len = match buf[lead] {
+                b if b >= 0xF0 => 4,
+                b if b >= 0xE0 => 3,
+                b if b >= 0xC0 => 2,
+                _ => 1,
+            };
+            return if lead + len > pos { lead } else { pos };
+        }
+        let mut p = pos;
+        while p > 0 && (buf[p] & 0xC0) == 0x80 {
+            p -= 1;
+        }
+        return p;
+    }
+    let Some(limit) = lead_limit(encoding) else {
+        return pos;
+    };
+    let anchor = buf[..pos].iter().rposition(|&b| b < limit).map(|i| i + 1).unwrap_or(0);
+    match step_chars(buf, anchor, pos, encoding) {
+        (_, p) if p == pos => pos,
+        (prev, _) => prev,
+    }
+}
+
+/// position in `buf` where a character starts, at or after `pos`
+pub fn char_boundary_after(buf: &[u8], pos: usize, encoding: &'static Encoding) -> usize {
+    if encoding == UTF_8 {
+        let mut p = pos;
+        while p < buf.len() && (buf[p] & 0xC0) == 0x80 {
+            p += 1;
+        }
+        return p;
+    }
+    let Some(limit) = lead_limit(encoding) else {
+        return pos;
+    };
+    match buf[..pos].iter().rposition(|&b| b < limit) {
+        Some(i) => step_chars(buf, i + 1, pos, encoding).1.min(buf.len()),
+        None => match buf[pos..].iter().position(|&b| b < limit) {
+            Some(i) => pos + i + 1,
+            None => pos,
+        },
+    }
+}
+
+/// `offset` moved forward to the start of a character
+pub fn snap_offset(idx: &LineIndex, file: &mut File, offset: u64) -> Result<u64> {
+    if offset == 0 || offset >= idx.sz {
+        return Ok(offset.min(idx.sz));
+    }
+    let start = offset.saturating_sub(SNAP_SZ as u64);
+    let mut buf = vec![0u8; 2 * SNAP_SZ];
+    file.seek(SeekFrom::Start(start))?;
+    let n = read_full(file, &mut buf)?;
+    buf.truncate(n);
+    Ok(start + char_boundary_after(&buf, (offset - start) as usize, idx.encoding()) as u64)
+}
+
+///
+/// read `line_cnt` lines starting at byte `offset` (line `line_no`) with at most `max_bytes`
+///
+/// A line longer than `max_bytes` is cut at a character boundary and continues on the
+/// next page at `next_offset`.
+pub fn read_page(idx: &LineIndex, file: &mut File, offset: u64, line_no: u64, line_cnt: u64, max_bytes: usize) -> Result<TextPage> {
+    let encoding = idx.encoding();
+    let want = (idx.sz.saturating_sub(offset) as usize).min(max_bytes);
+    let mut buf = vec![0u8; want];
+    file.seek(SeekFrom::Start(offset))?;
+    let n = read_full(file, &mut buf)?;
+    buf.truncate(n);
+
+    let mut end = n;
+    let mut lines = 0u64;
+    let mut last_nl: Option<usize> = None;
+    for i in memchr::memchr_iter(b'\n', &buf) {
+        if lines == line_cnt {
+            break;
+        }
+        lines += 1;
+        last_nl = Some(i);
+    }
+    let complete = lines;
+    if lines == line_cnt {
+        end = last_nl.map(|i| i + 1).unwrap_or(0);
+    } else if offset + (n as u64) < idx.sz {
+        // page is full: keep whole lines, cut a line longer than the page
+        match last_nl {
+            Some(i) => end = i + 1,
+            None => {
+                end = char_boundary_before(&buf, n, encoding);
+                if end == 0 {
+                    end = n;
+                }
+                lines = 1;
+            }
+        }
+    } else if n > 0 && last_nl != Some(n - 1) {
+        lines += 1;
+    }
+    let page = &buf[..end];
+    let (text, had_errors) = if offset == 0 {
+        encoding.decode_with_bom_removal(page)
+    } else {
+        encoding.decode_without_bom_handling(page)
+    };
+    let next_offset = offset + end as u64;
+    Ok(TextPage {
+        path: idx.path.clone(),
+        enc: encoding.name().to_string(),
+        sz: idx.sz,
+        tot_lines: idx.line_cnt(),
+        offset,
+        next_offset,
+        line_start: line_no,
+        next_line: line_no + complete,
+        line_cnt: lines,
+        had_errors,
+        eof: next_offset >= idx.sz,
+        text: text.into_owned(),
+    })
+}
+
+
+///
+/// follows a growing file like `tail -f`
+///
+/// `poll` returns the lines appended since the last call. A file that got shorter is read
+/// again from the top (`Truncated`), a new file under the same name (`Rotated`) after the
+/// rest of the old one.
+pub struct Tailer {
+    path: PathBuf,
+    file: Option<File>,
+    file_id: Option<(u64, u64)>,
+    pos: u64,
+    encoding: Option<&'static Encoding>,
+    decoder: Option<Decoder>,
+    /// decoded text after the last newline
+    partial: String,
+}
+
+impl Tailer {
+    /// start at the end of `path`, or at the top if it does not exist yet
+    pub fn new(path: &Path, encoding: Option<&'static Encoding>) -> Result<Tailer> {
+        let mut tailer = Tailer {
+            path: path.to_path_buf(),
+            file: None,
+            file_id: None,
+            pos: 0,
+            encoding,
+            decoder: None,
+            partial: String::new(),
+        };
+        if let Ok(metadata) = path.metadata() {
+            let mut file = File::open(path)?;
+            if tailer.encoding.is_none() && metadata.len() > 0 {
+                tailer.encoding = Some(detect_encoding(path)?);
+            }
+            tailer.pos = file.seek(SeekFrom::End(0))?;
+            tailer.file = Some(file);
+            tailer.file_id = Some(file_id(&metadata));
+        }
+        Ok(tailer)
+    }
+
+    pub fn poll(&mut self) -> Result<Vec<TailEvent>> {
+        let mut events = vec![];
+        let metadata = match self.path.metadata() {
+            Ok(metadata) => metadata,
+            // renamed away, the new file is not there yet
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(events),
+            Err(err) => return Err(err.into()),
+        };
+        let id = file_id(&metadata);
+        if self.file_id != Some(id) {
+            if self.file.is_some() {
+                self.read_lines(&mut events)?;
+                self.flush_partial(&mut events);
+                events.push(TailEvent { kind: TailKind::Rotated, lines: None });
+            }
+            self.file = Some(File::open(&self.path)?);
+            self.file_id = Some(id);
+            self.reset();
+        } else if metadata.len() < self.pos {
+            events.push(TailEvent { kind: TailKind::Truncated, lines: None });
+            self.reset();
+        }
+        self.read_lines(&mut events)?;
+        Ok(events)
+    }
+
+    fn reset(&mut self) {
+        self.pos = 0;
+        self.decoder = None;
+        self.partial.clear();
+    }
+
+    fn read_lines(&mut self, events: &mut Vec<TailEvent>) -> Result<()> {
+        let Some(file) = self.file.as_mut() else {
+            return Ok(());
+        };
+        file.seek(SeekFrom::Start(self.pos))?;
+        let mut buf = vec![0u8; CHUNK_SZ];
+        let mut lines = vec![];
+        loop {
+            let n = file.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            self.pos += n as u64;
+            let encoding = *self.encoding.get_or_insert_with(|| detect_bytes(&buf[..n], false));
+            let decoder = self.decoder.get_or_insert_with(|| encoding.new_decoder_with_bom_removal());
+            let mut text = String::with_capacity(decoder.max_utf8_buffer_length(n).unwrap_or(n * 3));
+            let _ = decoder.decode_to_string(&buf[..n], &mut text, false);
+            self.partial.push_str(&text);
+            if let Some(last_nl) = self.partial.rfind('\n') {
+                let rest = self.partial.split_off(last_nl + 1);
+                let done = std::mem::replace(&mut self.partial, rest);
+                lines.extend(done.lines().map(|line| line.to_string()));
+            }
+        }
+        if !lines.is_empty() {
+            events.push(TailEvent { kind: TailKind::Lines, lines: Some(lines) });
+        }
+        Ok(())
+    }
+
+    fn flush_partial(&mut self, events: &mut Vec<TailEvent>) {
+        if !self.partial.is_empty() {
+            let line = std::mem::take(&mut self.partial);
+            events.push(TailEvent { kind: TailKind::Lines, lines: Some(vec![line]) });
+        }
+    }
+}
+
+/// inode where there is one, else the creation time
+fn file_id(metadata: &std::fs::Metadata) -> (u64, u64) {
+    if let Some(inode) = metadata.inode() {
+        return (inode, 0);
+    }
+    match metadata.created().ok().and_then(|tm| tm.duration_since(std::time::UNIX_EPOCH).ok()) {
+        Some(d) => (d.as_secs(), d.subsec_nanos() as u64),
+        None => (0, 0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_char_boundary() {
+        let s = "ab한글".as_bytes();
+        assert_eq!(char_boundary_before(s, 3, UTF_8), 2);
+        assert_eq!(char_boundary_before(s, 5, UTF_8), 5);
+        assert_eq!(char_boundary_after(s, 3, UTF_8), 5);
+
+        let (euc, _, _) = EUC_KR.encode("a 한글b");
+        assert_eq!(char_boundary_before(&euc, 3, EUC_KR), 2);
+        assert_eq!(char_boundary_before(&euc, 5, EUC_KR), 4);
+        assert_eq!(char_boundary_after(&euc, 3, EUC_KR), 4);
+        assert_eq!(char_boundary_after(&euc, 4, EUC_KR), 4);
+
+        let (sjis, _, _) = SHIFT_JIS.encode(" ｱ日本");
+        assert_eq!(char_boundary_after(&sjis, 3, SHIFT_JIS), 4);
+        assert_eq!(char_boundary_before(&sjis, 5, SHIFT_JIS), 4);
+    }
+
+    #[test]
+    fn test_line_index() {
+        let base = std::env::temp_dir().join("napi-folder-test-line-index");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let path = base.join("a.log");
+        let text: String = (0..3000).map(|i| format!("line {}\n", i)).collect();
+        std::fs::write(&path, &text).unwrap();
+
+        let idx = LineIndex::load_or_build(&path, &base.join("idx"), None).unwrap();
+        assert_eq!(idx.line_cnt(), 3000);
+        assert_eq!(idx.checkpoints.len(), 3);
+        let mut file = File::open(&path).unwrap();
+        let offset = idx.line_offset(&mut file, 2500).unwrap();
+        assert_eq!(offset as usize, text.find("line 2500\n").unwrap());
+        assert_eq!(idx.line_at(&mut file, offset + 3).unwrap(), (2500, offset));
+
+        // appended lines extend the saved index
+        std::fs::write(&path, format!("{}last", text)).unwrap();
+        let idx = LineIndex::load_or_build(&path, &base.join("idx"), None).unwrap();
+        assert_eq!(idx.line_cnt(), 3001);
+
+        // a rewrite to the same or a larger size is indexed again
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        let text: String = (0..3000).map(|i| format!("LINE {}\n", i)).collect();
+        std::fs::write(&path, format!("{}LAST", text)).unwrap();
+        let idx = LineIndex::load_or_build(&path, &base.join("idx"), Some(idx)).unwrap();
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(idx.line_offset(&mut file, 2500).unwrap() as usize, text.find("LINE 2500\n").unwrap());
+        let text: String = (0..2000).map(|i| format!("a longer line {}\n", i)).collect();
+        std::fs::write(&path, &text).unwrap();
+        let idx = LineIndex::load_or_build(&path, &base.join("idx"), Some(idx)).unwrap();
+        assert_eq!(idx.line_cnt(), 2000);
+        let mut file = File::open(&path).unwrap();
+        assert_eq!(idx.line_offset(&mut file, 1500).unwrap() as usize, text.find("a longer line 1500\n").unwrap());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_tailer() {
+        let base = std::env::temp_dir().join("napi-folder-test-tailer");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let path = base.join("a.log");
+        std::fs::write(&path, "old\n").unwrap();
+        let mut tailer = Tailer::new(&path, None).unwrap();
+        assert!(tailer.poll().unwrap().is_empty());
+
+        let append = |bytes: &[u8]| {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+            std::io::Write::write_all(&mut file, bytes).unwrap();
+        };
+        let line_bytes = "새 줄\r\n".as_bytes();
+        append(&line_bytes[..4]);
+        assert!(tailer.poll().unwrap().is_empty());
+        append(&line_bytes[4..]);
+        append(b"second\nthi");
+        let events = tailer.poll().unwrap();
+        assert_eq!(events, vec![TailEvent { kind: TailKind::Lines, lines: Some(vec!["새 줄".to_string(), "second".to_string()]) }]);
+
+        std::fs::write(&path, "x\n").unwrap();
+        let events = tailer.poll().unwrap();
+        assert_eq!(events[0].kind, TailKind::Truncated);
+        assert_eq!(events[1].lines, Some(vec!["x".to_string()]));
+
+        std::fs::rename(&path, base.join("a.log.1")).unwrap();
+        assert!(tailer.poll().unwrap().is_empty());
+        std::fs::write(&path, "rotated\n").unwrap();
+        let events = tailer.poll().unwrap();
+        let kinds: Vec<TailKind> = events.iter().map(|event| event.kind.clone()).collect();
+        #[cfg(unix)]
+        assert_eq!(kinds, vec![TailKind::Rotated, TailKind::Lines]);
+        assert_eq!(events.last().unwrap().lines, Some(vec!["rotated".to_string()]));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_detect_candidates() {
+        let text = "한글 텍스트 파일입니다. ".repeat(20);
+        let (euc, _, _) = EUC_KR.encode(&text);
+        let (guess, confident, candidates) = detect_candidates(&euc, true);
+        assert_eq!((guess, confident), (EUC_KR, true));
+        assert_eq!(candidates[0], EncCandidate { enc: "EUC-KR".to_string(), err_cnt: 0 });
+        assert!(candidates.len() <= MAX_CANDIDATES);
+
+        let mut broken = euc.into_owned();
+        broken.insert(10, 0xff);
+        let (guess, confident, candidates) = detect_candidates(&broken, true);
+        assert_eq!((guess, confident), (EUC_KR, false));
+        assert_eq!(candidates[0], EncCandidate { enc: "EUC-KR".to_string(), err_cnt: 2 });
+
+        let (latin, _, _) = encoding_rs::WINDOWS_1252.encode("café crème brûlée, déjà vu");
+        assert_eq!(detect_candidates(&latin, true).0, encoding_rs::WINDOWS_1252);
+        assert_eq!(count_errors(UTF_8, b"ab\xffc\xfe", true), 2);
+        // a sample cut in the middle of a character
+        let (euc, _, _) = EUC_KR.encode("한글");
+        assert_eq!(count_errors(EUC_KR, &euc[..3], false), 0);
+        assert_eq!(detect_candidates(&euc[..3], false).2[0].err_cnt, 0);
+    }
+
+    #[test]
+    fn test_detect_bom() {
+        let text = "café crème, 한글 텍스트\n".repeat(20);
+        for encoding in [UTF_16LE, UTF_16BE, UTF_8] {
+            let bytes = encode_text(&text, encoding, true).unwrap();
+            assert_eq!(detect_bytes(&bytes, true), encoding);
+            assert_eq!(detect_bytes(&bytes[..3], false), encoding);
+            let (guess, confident, candidates) = detect_candidates(&bytes, true);
+            assert_eq!((guess, confident), (encoding, true));
+            assert_eq!(candidates[0], EncCandidate { enc: encoding.name().to_string(), err_cnt: 0 });
+        }
+    }
+
+    #[test]
+    fn test_line_ending() {
+        assert_eq!(detect_line_ending("a\r\nb\r\nc\n"), Some(LineEnding::Crlf));
+        assert_eq!(detect_line_ending("a\rb\rc\r\n"), Some(LineEnding::Cr));
+        assert_eq!(detect_line_ending("abc"), None);
+        assert_eq!(convert_line_ending("a\r\nb\rc\n", LineEnding::Crlf), "a\r\nb\r\nc\r\n");
+    }
+
+    #[test]
+    fn test_encode_text() {
+        assert_eq!(encode_text("가a", EUC_KR, false).unwrap(), b"\xb0\xa1a");
+        assert_eq!(encode_text("a", UTF_8, true).unwrap(), b"\xef\xbb\xbfa");
+        assert_eq!(encode_text("a", UTF_16LE, true).unwrap(), b"\xff\xfea\x00");
+        assert!(encode_text("a😀", EUC_KR, false).is_err());
+    }
+
+    #[test]
+    fn test_text_stats() {
+        let stats = text_stats("fn main() {\r\n    let a = 1; \r\n\tb();\n}");
+        assert_eq!((stats.line_cnt, stats.word_cnt, stats.char_cnt), (4, 9, 37));
+        assert_eq!((stats.mixed_ending, stats.ends_with_nl), (true, false));
+        assert_eq!((stats.indent, stats.tab_lines, stats.space_lines), (Some(Indent::Mixed), 1, 1));
+        assert_eq!((stats.trailing_ws_lines, stats.longest_line, stats.longest_line_no), (1, 15, 1));
+
+        let stats = text_stats("a\rbb\r");
+        assert_eq!((stats.line_cnt, stats.longest_line, stats.ends_with_nl), (2, 2, true));
+        assert_eq!(text_stats("").line_cnt, 0);
+    }
+}
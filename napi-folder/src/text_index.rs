@@ -0,0 +1,401 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::SystemTime;
+use encoding_rs::{Encoding, UTF_8};
+use rayon::prelude::*;
+use serde::{Serialize, Deserialize};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::models::{ApiError, TextIndexInfo, TextIndexOpt};
+use crate::search::{Matcher, glob_hit, is_binary};
+use crate::system_time_ext::SystemTimeExt;
+use crate::text::{detect_bytes, load_index, save_index, DETECT_SZ};
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// longer words (hashes, base64) are not indexed
+const MAX_TERM_LEN: usize = 64;
+pub const DEFAULT_MAX_FILE_SZ: u64 = 4 * 1024 * 1024;
+/// BM25 parameters
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct IndexedDoc {
+    /// `/` separated, relative to the root
+    pub rel: String,
+    pub sz: u64,
+    pub tm: u64,
+    pub enc: String,
+    /// words in the document
+    pub len: u32,
+}
+
+impl IndexedDoc {
+    pub fn encoding(&self) -> &'static Encoding {
+        Encoding::for_label(self.enc.as_bytes()).unwrap_or(UTF_8)
+    }
+}
+
+///
+/// inverted index of the words of the text files under a root
+///
+/// `terms` maps each lowercased word to `(doc id, occurrences)`, doc ids ascending. Query words match
+/// the indexed words they are a prefix of, so `문서` finds `문서를` and `spec` finds `specs`. All query
+/// words must match, documents are ranked with BM25. `update` re-reads only files whose size or mtime
+/// changed and drops the postings of files that are gone.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct TextIndex {
+    pub root: String,
+    /// seconds, when the index was updated
+    pub tm: u64,
+    /// the options of the last update, used again if an update gives none
+    pub opt: TextIndexOpt,
+    pub docs: Vec<IndexedDoc>,
+    pub terms: BTreeMap<String, Vec<(u32, u32)>>,
+    /// binary files with their `(sz, tm)`, not read again while unchanged
+    #[serde(default)]
+    pub binaries: HashMap<String, (u64, u64)>,
+}
+
+/// running totals of `TextIndex::update`, read by the progress reporter
+#[derive(Default)]
+pub struct TextIndexStat {
+    pub docs: AtomicU64,
+    pub read_docs: AtomicU64,
+}
+
+impl TextIndexStat {
+    pub fn to_info(&self, root: &str) -> TextIndexInfo {
+        TextIndexInfo {
+            root: root.to_string(),
+            docs: self.docs.load(Ordering::Relaxed),
+            read_docs: Some(self.read_docs.load(Ordering::Relaxed)),
+            ..TextIndexInfo::default()
+        }
+    }
+}
+
+enum DocRead {
+    Text(IndexedDoc, HashMap<String, u32>),
+    Binary,
+}
+
+/// a file found by the walk
+struct DocFile {
+    rel: String,
+    path: PathBuf,
+    sz: u64,
+    tm: u64,
+}
+
+impl TextIndex {
+    pub fn info(&self) -> TextIndexInfo {
+        TextIndexInfo {
+            root: self.root.clone(),
+            docs: self.docs.len() as u64,
+            terms: self.terms.len() as u64,
+            tm: self.tm,
+            ..TextIndexInfo::default()
+        }
+    }
+
+    /// the saved index of `root`, `None` if there is none or it can't be read
+    pub fn load(root: &str, index_dir: &Path) -> Option<TextIndex> {
+        load_index(index_dir, root).filter(|idx: &TextIndex| idx.root == root)
+    }
+
+    pub fn save(&self, index_dir: &Path) -> Result<()> {
+        save_index(index_dir, &self.root, self)
+    }
+
+    ///
+    /// index the text files under `root`, keeping the documents of `prev` that are unchanged
+    ///
+    /// Binary files, files over `max_file_sz` and symlinks are skipped. Returns `None` if canceled.
+    pub fn update(root: &Path, prev: Option<&TextIndex>, opt: &TextIndexOpt, stat: &TextIndexStat, cancel: &AtomicBool) -> Option<TextIndex> {
+        let files = Mutex::new(vec![]);
+        walk_dir(root, "", opt, &files, cancel);
+        let mut files = files.into_inner().unwrap_or_default();
+        files.sort_by(|a, b| a.rel.cmp(&b.rel));
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let current: HashMap<&str, &DocFile> = files.iter().map(|file| (file.rel.as_str(), file)).collect();
+        let mut docs: Vec<IndexedDoc> = vec![];
+        // old doc id -> new doc id, in the order of the old ids so postings stay sorted
+        let mut kept: HashMap<u32, u32> = HashMap::new();
+        for (id, doc) in prev.iter().flat_map(|prev| prev.docs.iter().enumerate()) {
+            if current.get(doc.rel.as_str()).is_some_and(|file| file.sz == doc.sz && file.tm == doc.tm) {
+                kept.insert(id as u32, docs.len() as u32);
+                docs.push(doc.clone());
+            }
+        }
+        let mut binaries: HashMap<String, (u64, u64)> = prev.map(|prev| prev.binaries.iter()
+            .filter(|(rel, sz_tm)| current.get(rel.as_str()).is_some_and(|file| (file.sz, file.tm) == **sz_tm))
+            .map(|(rel, sz_tm)| (rel.clone(), *sz_tm))
+            .collect())
+            .unwrap_or_default();
+        let changed: Vec<&DocFile> = {
+            let kept_rels: HashSet<&str> = docs.iter().map(|doc| doc.rel.as_str()).collect();
+            files.iter()
+                .filter(|file| !kept_rels.contains(file.rel.as_str()) && !binaries.contains_key(&file.rel))
+                .collect()
+        };
+        stat.docs.store(docs.len() as u64, Ordering::Relaxed);
+
+        let read: Vec<(&DocFile, DocRead)> = changed.par_iter()
+            .filter_map(|file| {
+                if cancel.load(Ordering::Relaxed) {
+                    return None;
+                }
+                let read = read_doc(file);
+                stat.read_docs.fetch_add(1, Ordering::Relaxed);
+                if let Some(DocRead::Text(..)) = read {
+                    stat.docs.fetch_add(1, Ordering::Relaxed);
+                }
+                Some((*file, read?))
+            })
+            .collect();
+        if cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut terms: BTreeMap<String, Vec<(u32, u32)>> = BTreeMap::new();
+        if let Some(prev) = prev {
+            for (term, postings) in prev.terms.iter() {
+                let postings: Vec<(u32, u32)> = postings.iter()
+                    .filter_map(|(id, tf)| kept.get(id).map(|new_id| (*new_id, *tf)))
+                    .collect();
+                if !postings.is_empty() {
+                    terms.insert(term.clone(), postings);
+                }
+            }
+        }
+        for (file, read) in read {
+            let DocRead::Text(doc, counts) = read else {
+                binaries.insert(file.rel.clone(), (file.sz, file.tm));
+                continue;
+            };
+            let id = docs.len() as u32;
+            docs.push(doc);
+            for (term, tf) in counts {
+                terms.entry(term).or_default().push((id, tf));
+            }
+        }
+        Some(TextIndex {
+            root: root.to_string_lossy().into_owned(),
+            tm: SystemTime::now().to_sec(),
+            opt: opt.clone(),
+            docs,
+            terms,
+            binaries,
+        })
+    }
+
+    ///
+    /// documents containing every word of `query`, best first, with their BM25 scores
+    ///
+    /// The second value is the number of matching documents.
+    pub fn search(&self, query: &str, max_results: usize) -> Result<(Vec<(&IndexedDoc, f64)>, usize)> {
+        let words = query_words(query);
+        if words.is_empty() {
+            return Err(ApiError::Folder(String::from("Err TextIndex: no words in the query")));
+        }
+        let doc_cnt = self.docs.len() as f64;
+        let avg_len = self.docs.iter().map(|doc| doc.len as f64).sum::<f64>() / doc_cnt.max(1.0);
+        let mut scores: Option<HashMap<u32, f64>> = None;
+        for word in words.iter() {
+            let mut tfs: HashMap<u32, u32> = HashMap::new();
+            for (_, postings) in self.terms.range(word.clone()..).take_while(|(term, _)| term.starts_with(word.as_str())) {
+                for (id, tf) in postings {
+                    *tfs.entry(*id).or_default() += tf;
+                }
+            }
+            let df = tfs.len() as f64;
+            let idf = (1.0 + (doc_cnt - df + 0.5) / (df + 0.5)).ln();
+            let word_scores = tfs.into_iter().map(|(id, tf)| {
+                let tf = tf as f64;
+                let len = self.docs[id as usize].len as f64;
+                (id, idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * len / avg_len.max(1.0))))
+            });
+            scores = Some(match scores {
+                None => word_scores.collect(),
+                Some(mut scores) => {
+                    let word_scores: HashMap<u32, f64> = word_scores.collect();
+                    scores.retain(|id, _| word_scores.contains_key(id));
+                    for (id, score) in scores.iter_mut() {
+                        *score += word_scores[id];
+                    }
+                    scores
+                }
+            });
+        }
+        let mut ranked: Vec<(&IndexedDoc, f64)> = scores.unwrap_or_default().into_iter()
+            .map(|(id, score)| (&self.docs[id as usize], score))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.rel.cmp(&b.0.rel)));
+        let total = ranked.len();
+        ranked.truncate(max_results);
+        Ok((ranked, total))
+    }
+}
+
+/// lowercased words of `text`, long ones dropped
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.unicode_words()
+        .filter(|word| word.chars().count() <= MAX_TERM_LEN)
+        .map(|word| word.to_lowercase())
+}
+
+/// distinct words of a query, in order
+pub fn query_words(query: &str) -> Vec<String> {
+    let mut seen: Vec<String> = vec![];
+    for word in words(query) {
+        if !seen.contains(&word) {
+            seen.push(word);
+        }
+    }
+    seen
+}
+
+/// matches the start of each word of `query`, for snippets
+pub fn word_matcher(query: &str) -> Result<Matcher> {
+    let alts: Vec<String> = query_words(query).iter().map(|word| regex::escape(word)).collect();
+    Matcher::new(&format!(r"\b(?:{})", alts.join("|")), true, false)
+}
+
+fn walk_dir(dir: &Path, rel: &str, opt: &TextIndexOpt, files: &Mutex<Vec<DocFile>>, cancel: &AtomicBool) {
+    if cancel.load(Ordering::Relaxed) {
+        return;
+    }
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    let hidden = opt.hidden.unwrap_or(false);
+    let max_file_sz = opt.max_file_sz.unwrap_or(DEFAULT_MAX_FILE_SZ);
+    let mut sub_dirs: Vec<(PathBuf, String)> = vec![];
+    let mut found: Vec<DocFile> = vec![];
+    for entry in entries.flatten() {
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if meta.file_type().is_symlink() {
+            continue;
+        }
+        let nm = entry.file_name().to_string_lossy().into_owned();
+        if !hidden && nm.starts_with('.') {
+            continue;
+        }
+        let sub_rel = if rel.is_empty() { nm.clone() } else { format!("{}/{}", rel, nm) };
+        if opt.exclude.iter().flatten().any(|pattern| glob_hit(pattern, &nm, &sub_rel)) {
+            continue;
+        }
+        if meta.is_dir() {
+            sub_dirs.push((entry.path(), sub_rel));
+        } else if meta.len() <= max_file_sz
+            && opt.include.as_ref().is_none_or(|include| include.is_empty() || include.iter().any(|pattern| glob_hit(pattern, &nm, &sub_rel))) {
+            found.push(DocFile {
+                rel: sub_rel,
+                path: entry.path(),
+                sz: meta.len(),
+                tm: meta.modified().map(|tm| tm.to_sec()).unwrap_or_default(),
+            });
+        }
+    }
+    if let Ok(mut files) = files.lock() {
+        files.extend(found);
+    }
+    sub_dirs.par_iter().for_each(|(sub_dir, sub_rel)| walk_dir(sub_dir, sub_rel, opt, files, cancel));
+}
+
+/// decoded words of a text file with their counts, `None` if it can't be read
+fn read_doc(file: &DocFile) -> Option<DocRead> {
+    let mut f = std::fs::File::open(&file.path).ok()?;
+    let mut bytes = vec![];
+    (&mut f).take(DETECT_SZ as u64).read_to_end(&mut bytes).ok()?;
+    if is_binary(&bytes) {
+        return Some(DocRead::Binary);
+    }
+    f.read_to_end(&mut bytes).ok()?;
+    let encoding = detect_bytes(&bytes[..bytes.len().min(DETECT_SZ)], bytes.len() <= DETECT_SZ);
+    let (text, encoding, _) = encoding.decode(&bytes);
+    let mut counts: HashMap<String, u32> = HashMap::new();
+    let mut len: u32 = 0;
+    for word in words(&text) {
+        *counts.entry(word).or_default() += 1;
+        len += 1;
+    }
+    Some(DocRead::Text(IndexedDoc {
+        rel: file.rel.clone(),
+        sz: file.sz,
+        tm: file.tm,
+        enc: encoding.name().to_string(),
+        len,
+    }, counts))
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rels(idx: &TextIndex, query: &str) -> Vec<String> {
+        idx.search(query, 10).unwrap().0.into_iter().map(|(doc, _)| doc.rel.clone()).collect()
+    }
+
+    #[test]
+    fn test_text_index() {
+        let base = std::env::temp_dir().join("napi-folder-test-text-index");
+        let index_dir = base.join("index");
+        let root = base.join("root");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(root.join("specs")).unwrap();
+        std::fs::write(root.join("specs/viewer.md"), "# Viewer spec\nThe viewer shows images. Viewer viewer.\n").unwrap();
+        std::fs::write(root.join("specs/search.md"), "# Search spec\nSearch shows hits in the viewer.\n").unwrap();
+        let notes = "회의록: 문서를 정리합니다.\n".repeat(8);
+        let (euc_kr, _, _) = encoding_rs::EUC_KR.encode(&notes);
+        std::fs::write(root.join("notes.txt"), euc_kr).unwrap();
+        std::fs::write(root.join("image.png"), [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0, 0]).unwrap();
+
+        let cancel = AtomicBool::new(false);
+        let stat = TextIndexStat::default();
+        let opt = TextIndexOpt::default();
+        let idx = TextIndex::update(&root, None, &opt, &stat, &cancel).unwrap();
+        assert_eq!(stat.read_docs.load(Ordering::Relaxed), 4);
+        assert_eq!(idx.docs.len(), 3);
+        assert_eq!(rels(&idx, "viewer"), vec!["specs/viewer.md", "specs/search.md"]);
+        assert_eq!(rels(&idx, "viewer SEARCH"), vec!["specs/search.md"]);
+        assert_eq!(rels(&idx, "spec"), vec!["specs/search.md", "specs/viewer.md"]);
+        assert_eq!(rels(&idx, "문서"), vec!["notes.txt"]);
+        assert!(rels(&idx, "missing").is_empty());
+        assert!(idx.search("  ..", 10).is_err());
+
+        idx.save(&index_dir).unwrap();
+        let saved = TextIndex::load(&idx.root, &index_dir).unwrap();
+        assert_eq!(saved.terms, idx.terms);
+
+        // only the new file is read, the removed one is dropped
+        std::fs::remove_file(root.join("specs/viewer.md")).unwrap();
+        std::fs::write(root.join("specs/tail.md"), "Tail follows the viewer log.\n").unwrap();
+        let stat = TextIndexStat::default();
+        let idx = TextIndex::update(&root, Some(&saved), &opt, &stat, &cancel).unwrap();
+        assert_eq!(stat.read_docs.load(Ordering::Relaxed), 1);
+        assert_eq!(rels(&idx, "viewer"), vec!["specs/tail.md", "specs/search.md"]);
+        assert!(!idx.terms.contains_key("images"));
+
+        let opt = TextIndexOpt { include: Some(vec![String::from("*.txt")]), ..TextIndexOpt::default() };
+        let idx = TextIndex::update(&root, Some(&idx), &opt, &TextIndexStat::default(), &cancel).unwrap();
+        assert_eq!(idx.docs.len(), 1);
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn test_word_matcher() {
+        let matcher = word_matcher("Spec 문서").unwrap();
+        assert_eq!(matcher.find_ranges("specs, inspect; 문서를"), vec![(0, 4), (16, 22)]);
+    }
+}
@@ -0,0 +1,182 @@
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use image::codecs::jpeg::JpegEncoder;
+use crate::models::{ApiError, ThumbFormat};
+use crate::path_ext::PathExt;
+
+type Result<T> = std::result::Result<T, ApiError>;
+
+/// freedesktop size folders and the longest side of their thumbnails
+const BUCKETS: [(&str, u32); 4] = [("normal", 128), ("large", 256), ("x-large", 512), ("xx-large", 1024)];
+pub const MAX_THUMB_SIZE: u32 = 1024;
+const JPEG_QUALITY: u8 = 85;
+
+/// the smallest freedesktop size folder holding thumbnails of at least `max_size`
+pub fn bucket(max_size: u32) -> (&'static str, u32) {
+    BUCKETS.iter()
+        .find(|(_, size)| *size >= max_size)
+        .copied()
+        .unwrap_or(BUCKETS[BUCKETS.len() - 1])
+}
+
+/// `file://` URI of an absolute path, escaped like glib's `g_filename_to_uri`
+pub fn file_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from(if path.starts_with('/') { "file://" } else { "file:///" });
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~!$&'()*+,=:@/".contains(&b) {
+            uri.push(b as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", b));
+        }
+    }
+    uri
+}
+
+/// `<thumb_dir>/<size folder>/<md5 of the URI>.png`
+pub fn thumb_path(thumb_dir: &Path, uri: &str, size_dir: &str) -> PathBuf {
+    thumb_dir.join(size_dir).join(format!("{:x}.png", md5::compute(uri.as_bytes())))
+}
+
+///
+/// PNG bytes of a saved thumbnail, if it was made from `uri` at `mtime`
+///
+/// Thumbnails of other programs are used too, they carry the same `Thumb::URI`/`Thumb::MTime`.
+pub fn load_cached(thumb_path: &Path, uri: &str, mtime: u64) -> Option<Vec<u8>> {
+    let bytes = std::fs::read(thumb_path).ok()?;
+    let reader = png::Decoder::new(Cursor::new(&bytes)).read_info().ok()?;
+    let text = &reader.info().uncompressed_latin1_text;
+    let value = |keyword: &str| text.iter().find(|chunk| chunk.keyword == keyword).map(|chunk| chunk.text.as_str());
+    let fresh = value("Thumb::URI") == Some(uri)
+        && value("Thumb::MTime").and_then(|tm| tm.parse::<u64>().ok()) == Some(mtime);
+    fresh.then_some(bytes)
+}
+
+///
+/// decode `path`, turned upright by its EXIF orientation, scaled down to fit `size`
+///
+/// The first frame of animations. Images smaller than `size` keep their size.
+pub fn make_thumbnail(path: &Path, size: u32) -> Result<DynamicImage> {
+    let err = |err: image::ImageError| ApiError::Folder(format!("Err Thumbnail: {}", err));
+    let mut decoder = ImageReader::open(path)?
+        .with_guessed_format()?
+        .into_decoder()
+        .map_err(err)?;
+    let orientation = decoder.orientation().map_err(err)?;
+    let mut img = DynamicImage::from_decoder(decoder).map_err(err)?;
+    img.apply_orientation(orientation);
+    Ok(fit(img, size))
+}
+
+/// scaled down to fit a `size` square, never up
+pub fn fit(img: DynamicImage, size: u32) -> DynamicImage {
+    if img.width() > size || img.height() > size {
+        img.thumbnail(size, size)
+    } else {
+        img
+    }
+}
+
+///
+/// save `img` as a freedesktop thumbnail of `uri` and return its PNG bytes
+///
+/// Written to a tmp file and renamed, readable only by the user (unix).
+pub fn save_thumbnail(img: &DynamicImage, thumb_path: &Path, uri: &str, mtime: u64) -> Result<Vec<u8>> {
+    let err = |err: png::EncodingError| ApiError::Folder(format!("Err Thumbnail: {}", err));
+    let rgba = img.to_rgba8();
+    let mut bytes = vec![];
+    let mut encoder = png::Encoder::new(&mut bytes, rgba.width(), rgba.height());
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.add_text_chunk(String::from("Thumb::URI"), uri.to_string()).map_err(err)?;
+    encoder.add_text_chunk(String::from("Thumb::MTime"), mtime.to_string()).map_err(err)?;
+    encoder.add_text_chunk(String::from("Software"), String::from("re-viewer")).map_err(err)?;
+    encoder.write_header().map_err(err)?
+        .write_image_data(rgba.as_raw()).map_err(err)?;
+
+    if let Some(dir) = thumb_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let tmp_path = thumb_path.tmp_sibling();
+    std::fs::write(&tmp_path, &bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&tmp_path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    std::fs::rename(&tmp_path, thumb_path)?;
+    Ok(bytes)
+}
+
+/// `img` as PNG or JPEG bytes; JPEG drops the alpha channel
+pub fn encode(img: &DynamicImage, format: ThumbFormat) -> Result<Vec<u8>> {
+    let err = |err: image::ImageError| ApiError::Folder(format!("Err Thumbnail: {}", err));
+    let mut bytes = vec![];
+    match format {
+        ThumbFormat::Png => img.write_to(&mut Cursor::new(&mut bytes), image::ImageFormat::Png).map_err(err)?,
+        ThumbFormat::Jpeg => img.to_rgb8()
+            .write_with_encoder(JpegEncoder::new_with_quality(&mut bytes, JPEG_QUALITY))
+            .map_err(err)?,
+    }
+    Ok(bytes)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImageView, RgbImage};
+
+    #[test]
+    fn test_bucket() {
+        assert_eq!(bucket(64), ("normal", 128));
+        assert_eq!(bucket(128), ("normal", 128));
+        assert_eq!(bucket(200), ("large", 256));
+        assert_eq!(bucket(5000), ("xx-large", 1024));
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(file_uri(Path::new("/home/a/My Photo #1.jpg")), "file:///home/a/My%20Photo%20%231.jpg");
+        assert_eq!(file_uri(Path::new("/사진.png")), "file:///%EC%82%AC%EC%A7%84.png");
+        assert_eq!(file_uri(Path::new(r"C:\pics\a.png")), "file:///C:/pics/a.png");
+        // the md5 of freedesktop's own example
+        assert_eq!(
+            thumb_path(Path::new("/t"), "file:///home/jens/photos/me.png", "normal"),
+            PathBuf::from("/t/normal/c6ee772d9e49320e97ec29a7eb5b1697.png"));
+    }
+
+    #[test]
+    fn test_thumbnail() {
+        let base = std::env::temp_dir().join("napi-folder-test-thumb");
+        let _ = std::fs::remove_dir_all(&base);
+        std::fs::create_dir_all(&base).unwrap();
+        let path = base.join("wide.png");
+        RgbImage::from_fn(400, 100, |x, _| image::Rgb([(x % 256) as u8, 0, 0])).save(&path).unwrap();
+
+        let img = make_thumbnail(&path, 128).unwrap();
+        assert_eq!(img.dimensions(), (128, 32));
+        assert_eq!(make_thumbnail(&path, 1024).unwrap().dimensions(), (400, 100));
+        assert!(make_thumbnail(&base.join("none.png"), 128).is_err());
+
+        let uri = file_uri(&path);
+        let thumb = thumb_path(&base.join("thumbnails"), &uri, "normal");
+        let bytes = save_thumbnail(&img, &thumb, &uri, 1000).unwrap();
+        // the same thumbnail saved at once by several requests
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| save_thumbnail(&img, &thumb, &uri, 1000).unwrap());
+            }
+        });
+        assert_eq!(load_cached(&thumb, &uri, 1000).unwrap(), bytes);
+        assert!(load_cached(&thumb, &uri, 1001).is_none());
+        assert!(load_cached(&thumb, "file:///other.png", 1000).is_none());
+
+        let jpeg = encode(&img, ThumbFormat::Jpeg).unwrap();
+        assert_eq!(&jpeg[..2], &[0xff, 0xd8]);
+        let png = encode(&img, ThumbFormat::Png).unwrap();
+        assert_eq!(image::load_from_memory(&png).unwrap().dimensions(), (128, 32));
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+}
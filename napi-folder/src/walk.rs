@@ -0,0 +1,159 @@
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::SystemTime;
+use rayon::prelude::*;
+use crate::models::{DirSize, UsageNode};
+
+/// running totals of a walk, read by the progress reporter while the walk goes on
+#[derive(Default)]
+pub struct WalkStat {
+    pub files: AtomicU64,
+    pub dirs: AtomicU64,
+    pub sz: AtomicU64,
+}
+
+impl WalkStat {
+    pub fn to_dir_size(&self, path: &str) -> DirSize {
+        DirSize {
+            path: path.to_string(),
+            sz: self.sz.load(Ordering::Relaxed),
+            files: self.files.load(Ordering::Relaxed),
+            dirs: self.dirs.load(Ordering::Relaxed),
+            canceled: None,
+        }
+    }
+}
+
+pub struct SizeWalker<'a> {
+    pub stat: &'a WalkStat,
+    pub cancel: &'a AtomicBool,
+    /// every directory with its mtime and total, for caching
+    pub sizes: Mutex<Vec<(PathBuf, SystemTime, DirSize)>>,
+}
+
+impl<'a> SizeWalker<'a> {
+    pub fn new(stat: &'a WalkStat, cancel: &'a AtomicBool) -> Self {
+        SizeWalker {
+            stat,
+            cancel,
+            sizes: Mutex::new(vec![]),
+        }
+    }
+
+    ///
+    /// total size of the files under `path`, sub folders in parallel
+    ///
+    /// Symlinks are counted by their own size and not followed.
+    /// Returns `None` if canceled.
+    pub fn walk(&self, path: &Path) -> Option<DirSize> {
+        let node = self.walk_node(path, 0, 0, 0)?;
+        Some(DirSize {
+            path: path.to_string_lossy().into_owned(),
+            sz: node.sz,
+            files: node.files,
+            dirs: node.dirs,
+            canceled: None,
+        })
+    }
+
+    ///
+    /// `walk` that keeps the `top_n` largest children of every folder down to `depth`
+    ///
+    /// The rest of the children are summed up in `others_sz`/`others_cnt`, `pct` is left to `update_pct`.
+    pub fn walk_usage(&self, path: &Path, top_n: usize, depth: usize) -> Option<UsageNode> {
+        self.walk_node(path, 0, top_n, depth)
+    }
+
+    fn walk_node(&self, path: &Path, level: usize, top_n: usize, depth: usize) -> Option<UsageNode> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return None;
+        }
+        let keep_children = level < depth;
+        let mut node = UsageNode {
+            nm: path.file_name().map(|nm| nm.to_string_lossy().into_owned()).unwrap_or_default(),
+            dir: true,
+            ..UsageNode::default()
+        };
+        let mut children: Vec<UsageNode> = vec![];
+        let mut sub_dirs: Vec<PathBuf> = vec![];
+        if let Ok(entries) = std::fs::read_dir(path) {
+            for entry in entries.flatten() {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    sub_dirs.push(entry.path());
+                } else if let Ok(metadata) = entry.metadata() {
+                    node.sz += metadata.len();
+                    node.files += 1;
+                    self.stat.sz.fetch_add(metadata.len(), Ordering::Relaxed);
+                    self.stat.files.fetch_add(1, Ordering::Relaxed);
+                    if keep_children {
+                        children.push(UsageNode {
+                            nm: entry.file_name().to_string_lossy().into_owned(),
+                            sz: metadata.len(),
+                            files: 1,
+                            ..UsageNode::default()
+                        });
+                    }
+                }
+            }
+        }
+        self.stat.dirs.fetch_add(sub_dirs.len() as u64, Ordering::Relaxed);
+
+        let subs: Vec<Option<UsageNode>> = sub_dirs.par_iter()
+            .map(|sub_dir| self.walk_node(sub_dir, level + 1, top_n, depth))
+            .collect();
+        for sub in subs {
+            let sub = sub?;
+            node.sz += sub.sz;
+            node.files += sub.files;
+            node.dirs += sub.dirs + 1;
+            if keep_children {
+                children.push(sub);
+            }
+        }
+        if let Ok(tm) = path.metadata().and_then(|m| m.modified()) {
+            if let Ok(mut sizes) = self.sizes.lock() {
+                sizes.push((path.to_path_buf(), tm, DirSize {
+                    path: path.to_string_lossy().into_owned(),
+                    sz: node.sz,
+                    files: node.files,
+                    dirs: node.dirs,
+                    canceled: None,
+                }));
+            }
+        }
+
+        if keep_children {
+            children.sort_by(|a, b| b.sz.cmp(&a.sz).then_with(|| a.nm.cmp(&b.nm)));
+            if children.len() > top_n {
+                let others = children.split_off(top_n);
+                node.others_sz = Some(others.iter().map(|other| other.sz).sum());
+                node.others_cnt = Some(others.len() as u64);
+            }
+            node.children = Some(children);
+        }
+        Some(node)
+    }
+}
+
+/// fill `pct` (of `root_sz`) and `pct_parent` of `node` and its children, rounded to 0.01
+pub fn update_pct(node: &mut UsageNode, root_sz: u64, parent_sz: u64) {
+    node.pct = to_pct(node.sz, root_sz);
+    node.pct_parent = to_pct(node.sz, parent_sz);
+    let sz = node.sz;
+    if let Some(children) = node.children.as_mut() {
+        for child in children.iter_mut() {
+            update_pct(child, root_sz, sz);
+        }
+    }
+}
+
+fn to_pct(sz: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    (sz as f64 * 10000.0 / total as f64).round() / 100.0
+}
@@ -0,0 +1,94 @@
+use std::path::{Path, PathBuf};
+use notify::EventKind;
+use notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::DebouncedEvent;
+use crate::models::{WatchEvent, WatchKind, DiskInfo, DiskEvent, DiskEventKind};
+
+/// Converts debounced notify events to `WatchEvent`s, dropping access and unknown events.
+pub fn to_watch_events(events: &[DebouncedEvent]) -> Vec<WatchEvent> {
+    let mut ret: Vec<WatchEvent> = vec![];
+    for event in events {
+        let kind = match event.kind {
+            EventKind::Create(_) => WatchKind::Created,
+            EventKind::Remove(_) => WatchKind::Removed,
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                ret.push(WatchEvent {
+                    kind: WatchKind::Renamed,
+                    path: event.paths[1].to_string_lossy().into_owned(),
+                    from: Some(event.paths[0].to_string_lossy().into_owned()),
+                });
+                continue;
+            }
+            // one-sided rename: the path left or entered the watched tree
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => WatchKind::Removed,
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => WatchKind::Created,
+            EventKind::Modify(_) => WatchKind::Modified,
+            _ => continue,
+        };
+        for path in event.paths.iter() {
+            let watch_event = WatchEvent {
+                kind: kind.clone(),
+                path: path.to_string_lossy().into_owned(),
+                from: None,
+            };
+            if !ret.contains(&watch_event) {
+                ret.push(watch_event);
+            }
+        }
+    }
+    ret
+}
+
+/// Folders whose listing is changed by `event`: the parents of the paths and the paths themselves.
+pub fn affected_folders(event: &WatchEvent) -> Vec<PathBuf> {
+    let mut paths: Vec<&str> = vec![event.path.as_str()];
+    if let Some(from) = event.from.as_ref() {
+        paths.push(from.as_str());
+    }
+    let mut ret: Vec<PathBuf> = vec![];
+    for p in paths {
+        let p = Path::new(p);
+        ret.push(p.to_path_buf());
+        if let Some(parent) = p.parent() {
+            ret.push(parent.to_path_buf());
+        }
+    }
+    ret
+}
+
+/// disks mounted or unmounted between two `get_disks` results, matched by mount point
+pub fn diff_disks(old: &[DiskInfo], new: &[DiskInfo]) -> Vec<DiskEvent> {
+    let removed = old.iter()
+        .filter(|disk| !new.iter().any(|d| d.path == disk.path))
+        .map(|disk| DiskEvent { kind: DiskEventKind::Removed, disk: disk.clone() });
+    let added = new.iter()
+        .filter(|disk| !old.iter().any(|d| d.path == disk.path))
+        .map(|disk| DiskEvent { kind: DiskEventKind::Added, disk: disk.clone() });
+    removed.chain(added).collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(path: &str) -> DiskInfo {
+        DiskInfo {
+            path: path.to_string(),
+            ..DiskInfo::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_disks() {
+        let old = vec![disk("/"), disk("/media/usb1")];
+        let new = vec![disk("/"), disk("/media/usb2")];
+        let events = diff_disks(&old, &new);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, DiskEventKind::Removed);
+        assert_eq!(events[0].disk.path, "/media/usb1");
+        assert_eq!(events[1].kind, DiskEventKind::Added);
+        assert_eq!(events[1].disk.path, "/media/usb2");
+        assert!(diff_disks(&new, &new).is_empty());
+    }
+}